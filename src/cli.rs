@@ -1,7 +1,6 @@
-use clap::{Parser, Subcommand};
-use serde::Serialize;
+use clap::{Args, Parser, Subcommand};
 
-const DEFAULT_CONFIG_PATH: &str = "$HOME/.config/mumowrk/config.yml";
+use crate::config::{BindingOptions, NotificationVerbosity};
 
 /// Multi Monitor Workspace Manager
 #[derive(Parser, Debug)]
@@ -10,31 +9,118 @@ pub struct Cli {
     #[command(subcommand)]
     pub command: Subcommands,
 
-    /// Path to config file
-    #[arg(short, long, value_name = "CONFIG_PATH", default_value = DEFAULT_CONFIG_PATH, required = false)]
-    pub config: String,
+    /// Path to config file (default: $MUMOWRK_CONFIG or mumowrk/config.{yml,yaml,toml,json}
+    /// in $XDG_CONFIG_HOME or $XDG_CONFIG_DIRS)
+    #[arg(short, long, value_name = "CONFIG_PATH", required = false)]
+    pub config: Option<String>,
+
+    /// Use the groups of this profile instead of selecting one by the active outputs
+    #[arg(short, long, value_name = "PROFILE", required = false)]
+    pub profile: Option<String>,
 
     /// Path to the IPC socket
     #[arg(short, long, value_name = "SOCKET_PATH", required = false)]
     pub socket: Option<String>,
 }
 
-#[derive(Debug, clap::ValueEnum, Clone, Default, Serialize, PartialEq)]
-#[serde(rename_all = "lowercase")]
-pub enum NotificationVerbosity {
-    #[default]
-    /// Don't show any notification
-    None,
-    /// Show only current workspace group index
-    Index,
-    /// Show the whole state summary
-    Summary,
+/// How `reorganize` handles workspaces whose monitor is inactive
+#[derive(Debug, clap::ValueEnum, Clone, Copy, PartialEq)]
+pub enum ReorganizeStrategy {
+    /// Merge their containers into the workspace on the main monitor of the monitor group
+    MergeToMain,
+    /// Move them to available monitors of the monitor group, park them on the fallback monitor
+    /// regardless of the fallback policy if the monitor group has no active monitors
+    SpreadToAvailable,
+    /// Leave them where they are, only move workspaces whose monitor is active
+    LeaveInPlace,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Validate the configuration and compare it against the active outputs
+    Check,
+    /// Generate a config with monitor groups for the active outputs
+    Generate {
+        /// Where to write the config, `-` for the standard output
+        /// (default: the config path, $XDG_CONFIG_HOME/mumowrk/config.yml if there is none)
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<String>,
+        /// Ask which monitor group each output should be in
+        #[arg(short, long)]
+        interactive: bool,
+        /// Overwrite the file if it exists
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Print sway bindings for the configured monitor groups to include in the sway config
+    SwayBindings {
+        #[command(flatten)]
+        options: BindingOptions,
+    },
+    /// Print the JSON Schema of the config file for editors
+    Schema,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SnapshotCommand {
+    /// Save a snapshot of the current windows and workspaces
+    Save,
+    /// List saved snapshots
+    List,
+    /// Show windows recorded in a snapshot
+    Show {
+        /// Snapshot ID (default: latest snapshot)
+        #[arg(value_name = "ID")]
+        id: Option<u128>,
+    },
+    /// Move windows back to the workspaces and monitors recorded in a snapshot
+    Restore {
+        /// Snapshot ID
+        #[arg(value_name = "ID")]
+        id: u128,
+    },
+}
+
+/// Criteria for selecting containers. Text values are regular expressions as in sway criteria.
+#[derive(Args, Debug, Default)]
+pub struct ContainerCriteria {
+    /// Match windows with a Wayland app_id
+    #[arg(long, value_name = "REGEX")]
+    pub app_id: Option<String>,
+    /// Match XWayland windows with a window class
+    #[arg(long, value_name = "REGEX")]
+    pub class: Option<String>,
+    /// Match windows with a title
+    #[arg(long, value_name = "REGEX")]
+    pub title: Option<String>,
+    /// Match the container with an ID
+    #[arg(long, value_name = "ID")]
+    pub con_id: Option<i64>,
+    /// Match containers with a mark
+    #[arg(long, value_name = "REGEX")]
+    pub mark: Option<String>,
+}
+
+impl ContainerCriteria {
+    /// No criteria given, the focused container should be used
+    pub fn is_empty(&self) -> bool {
+        self.app_id.is_none()
+            && self.class.is_none()
+            && self.title.is_none()
+            && self.con_id.is_none()
+            && self.mark.is_none()
+    }
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Subcommands {
     /// Initialize workspaces based on the configuration
-    Init,
+    Init {
+        /// Also execute the startup commands of the monitor groups, only once per session
+        /// (e.g. from `exec` in the sway config, not when switching profiles)
+        #[arg(long)]
+        startup: bool,
+    },
     /// Switch between workspace groups
     Switch {
         /// Absolute index or relative increment ([+-]N) for destination workspace group
@@ -43,11 +129,12 @@ pub enum Subcommands {
         /// Target monitor group name (default: first group in config)
         #[arg(short, long, value_name = "MONITOR_GROUP", required = false)]
         mon_group: Option<String>,
-        /// Send a notification about the new state (if used as flag, it will be set to `index`)
-        #[arg(short, long, value_name = "LEVEL", default_missing_value = "index", default_value = "none", num_args = 0..=1)]
-        notify: NotificationVerbosity,
+        /// Send a notification about the new state (if used as flag, it will be set to `index`,
+        /// default: `notify` setting of the monitor group)
+        #[arg(short, long, value_name = "LEVEL", default_missing_value = "index", num_args = 0..=1)]
+        notify: Option<NotificationVerbosity>,
     },
-    /// Move (reorder) workspace groups
+    /// Move (reorder) workspace groups by swapping them (default) or by inserting one at a new index
     #[clap(visible_alias = "swap")]
     MoveGroup {
         // TODO: change `from` and `to` to `A` and `B` or something
@@ -62,8 +149,23 @@ pub enum Subcommands {
         /// Target monitor group name (default: focused group)
         #[arg(short, long, value_name = "MONITOR_GROUP", required = false)]
         mon_group: Option<String>,
+        /// Insert the workspace group at the destination index and shift the groups in between by one
+        #[arg(short, long)]
+        insert: bool,
+    },
+    /// Merge all containers of a workspace group into another workspace group
+    Merge {
+        /// Absolute index of the workspace group to empty
+        #[arg(value_name = "FROM")]
+        from: i32,
+        /// Absolute index or relative increment ([+-]N) for destination workspace group
+        #[arg(value_name = "TO", allow_hyphen_values = true)]
+        to: String,
+        /// Target monitor group name (default: first group in config)
+        #[arg(short, long, value_name = "MONITOR_GROUP", required = false)]
+        mon_group: Option<String>,
     },
-    /// Move containers between workspace groups
+    /// Move containers between workspace groups keeping them on the same monitor index
     #[clap(visible_alias = "move")]
     MoveContainer {
         /// Absolute index or relative increment ([+-]N) for destination workspace group
@@ -75,9 +177,90 @@ pub enum Subcommands {
         /// Target monitor group name (default: focused group)
         #[arg(short, long, value_name = "MONITOR_GROUP", required = false)]
         mon_group: Option<String>,
+        /// Move all containers matching these criteria instead of the focused one
+        #[command(flatten)]
+        criteria: ContainerCriteria,
+    },
+    /// Rotate workspaces of a workspace group across the monitors of a monitor group
+    Rotate {
+        /// Absolute index or relative increment ([+-]N) of the workspace group (default: visible group)
+        #[arg(value_name = "INDEX", allow_hyphen_values = true)]
+        index: Option<String>,
+        /// Rotate in the opposite direction (right to left)
+        #[arg(short, long)]
+        reverse: bool,
+        /// Target monitor group name (default: first group in config)
+        #[arg(short, long, value_name = "MONITOR_GROUP", required = false)]
+        mon_group: Option<String>,
+    },
+    /// Exchange workspaces of a workspace group between two monitors of a monitor group
+    SwapMonitors {
+        /// Index of the first monitor within the monitor group
+        #[arg(value_name = "A")]
+        monitor_a: usize,
+        /// Index of the second monitor within the monitor group
+        #[arg(value_name = "B")]
+        monitor_b: usize,
+        /// Absolute index or relative increment ([+-]N) of the workspace group (default: visible group)
+        #[arg(value_name = "INDEX", allow_hyphen_values = true)]
+        index: Option<String>,
+        /// Target monitor group name (default: first group in config)
+        #[arg(short, long, value_name = "MONITOR_GROUP", required = false)]
+        mon_group: Option<String>,
+    },
+    /// Transfer a workspace group from one monitor group to another
+    Transfer {
+        /// Absolute index of the workspace group to transfer
+        #[arg(value_name = "INDEX")]
+        index: i32,
+        /// Source monitor group name
+        #[arg(long, value_name = "MONITOR_GROUP")]
+        from: String,
+        /// Destination monitor group name
+        #[arg(long, value_name = "MONITOR_GROUP")]
+        to: String,
+        /// Index of the workspace group in the destination monitor group (default: INDEX)
+        #[arg(long = "as", value_name = "NEW_INDEX", allow_hyphen_values = true)]
+        as_index: Option<i32>,
+    },
+    /// Renumber workspace groups to a contiguous range preserving their order
+    Compact {
+        /// Target monitor group name (default: first group in config)
+        #[arg(short, long, value_name = "MONITOR_GROUP", required = false)]
+        mon_group: Option<String>,
+    },
+    /// Remove empty workspaces that are not visible
+    Gc {
+        /// Only remove workspaces of this monitor group (all monitor groups by default)
+        #[arg(short, long, value_name = "MONITOR_GROUP")]
+        mon_group: Option<String>,
     },
     /// Reorganize containers after a change to monitor configuration (e.g. switching monitors)
-    Reorganize {},
+    Reorganize {
+        /// Only print the planned actions without executing them
+        #[arg(long)]
+        dry_run: bool,
+        /// Print the planned actions as JSON (requires --dry-run)
+        #[arg(long, requires = "dry_run")]
+        json: bool,
+        /// Print the actions while executing them
+        #[arg(short, long, conflicts_with = "dry_run")]
+        verbose: bool,
+        /// Only reorganize this monitor group (all monitor groups by default)
+        #[arg(short, long, value_name = "MONITOR_GROUP")]
+        mon_group: Option<String>,
+        /// Only reorganize the workspace group with this index
+        #[arg(short, long, value_name = "N", allow_hyphen_values = true)]
+        index: Option<i32>,
+        /// Override how workspaces whose monitor is inactive are handled
+        #[arg(short, long)]
+        strategy: Option<ReorganizeStrategy>,
+    },
+    /// Save, list, show and restore snapshots of windows and workspaces
+    Snapshot {
+        #[command(subcommand)]
+        command: SnapshotCommand,
+    },
     /// Print the current state of workspaces
     Print {
         /// Print state as JSON input for a waybar module
@@ -87,4 +270,9 @@ pub enum Subcommands {
         #[arg(long, short)]
         subscribe: bool,
     },
+    /// Work with the configuration file
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
 }
@@ -0,0 +1,153 @@
+use clap::Args;
+
+use crate::workspaces::WorkspaceId;
+use super::config::Config;
+use super::monitor_group::MonitorGroup;
+
+
+/// Workspace group indices bound to number keys
+const BOUND_INDICES: std::ops::RangeInclusive<i32> = 1..=9;
+
+/// Default additional modifiers of the monitor groups in the order of the config
+const DEFAULT_GROUP_MODIFIERS: [&str; 2] = ["", "Mod1"];
+
+/// Keys and modifiers of generated sway bindings. Switching uses `MODIFIER+GROUP_MODIFIER+KEY`,
+/// moving the focused container adds the move modifier and following it the focus modifier.
+#[derive(Args, Debug)]
+pub struct BindingOptions {
+    /// Modifier of all bindings
+    #[arg(long, value_name = "MODIFIER", default_value = "$mod")]
+    pub modifier: String,
+    /// Additional modifier for the bindings of a monitor group, can be repeated
+    /// (default: none for the first monitor group, Mod1 for the second one)
+    #[arg(short, long = "group-modifier", value_name = "GROUP=MODIFIER")]
+    pub group_modifiers: Vec<String>,
+    /// Additional modifier for moving the focused container
+    #[arg(long, value_name = "MODIFIER", default_value = "Shift")]
+    pub move_modifier: String,
+    /// Additional modifier for moving the focused container and following it
+    #[arg(long, value_name = "MODIFIER", default_value = "Ctrl")]
+    pub focus_modifier: String,
+    /// Key for the previous workspace group
+    #[arg(long, value_name = "KEY", default_value = "Prior")]
+    pub prev_key: String,
+    /// Key for the next workspace group
+    #[arg(long, value_name = "KEY", default_value = "Next")]
+    pub next_key: String,
+    /// Key for swapping the visible workspaces of the first two monitors of a monitor group
+    #[arg(long, value_name = "KEY", default_value = "Tab")]
+    pub swap_key: String,
+    /// Also assign the workspaces to their monitors with `workspace ... output ...`.
+    /// Patterns are assigned to the outputs they match now, the bindings have to be
+    /// generated again when the outputs change. Patterns without an active output are skipped.
+    #[arg(long)]
+    pub assign_outputs: bool,
+}
+
+/// Join modifiers and a key to a sway key combination, skipping empty modifiers
+fn key_combination(parts: &[&str]) -> String {
+    parts.iter()
+        .filter(|part| !part.is_empty())
+        .copied()
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+/// Additional modifier of every monitor group, given with `--group-modifier GROUP=MODIFIER`
+/// or the default for its position
+fn get_group_modifiers(config: &Config, options: &BindingOptions) -> Result<Vec<String>, String> {
+    let mut given = Vec::new();
+    for group_modifier in options.group_modifiers.iter() {
+        let (name, modifier) = group_modifier.split_once('=')
+            .ok_or(format!("Invalid group modifier {}, expected GROUP=MODIFIER", group_modifier))?;
+        if config.get_group(name).is_none() {
+            return Err(format!("Monitor group {} is not configured", name));
+        }
+        given.push((name, modifier));
+    }
+    config.groups.iter()
+        .enumerate()
+        .map(|(index, group)| given.iter()
+            .find(|(name, _)| *name == group.name)
+            .map(|(_, modifier)| modifier.to_string())
+            .or(DEFAULT_GROUP_MODIFIERS.get(index).map(|modifier| modifier.to_string()))
+            .ok_or(format!("No modifier for monitor group {}, set it with --group-modifier {}=MODIFIER", group.name, group.name)))
+        .collect()
+}
+
+fn format_group_bindings(group: &MonitorGroup, group_modifier: &str, options: &BindingOptions) -> Vec<String> {
+    let name = &group.name;
+    let settings = &group.settings;
+    let indices: Vec<i32> = BOUND_INDICES
+        .filter(|index| settings.first_index.is_none_or(|first| *index >= first))
+        .filter(|index| settings.last_index.is_none_or(|last| *index <= last))
+        .collect();
+    let keys: Vec<(String, String)> = indices.iter()
+        .map(|index| (index.to_string(), index.to_string()))
+        .chain([
+            (options.prev_key.clone(), "-1".to_string()),
+            (options.next_key.clone(), "+1".to_string()),
+        ])
+        .collect();
+    let modifiers = [options.modifier.as_str(), group_modifier];
+    let bind = |extra_modifiers: &[&str], key: &str, command: String| format!(
+        "bindsym {} exec $mumowrk {}",
+        key_combination(&[&modifiers[..], extra_modifiers, &[key]].concat()),
+        command,
+    );
+
+    let mut lines = vec![format!("# monitor group {}", name)];
+    lines.extend(keys.iter()
+        .map(|(key, destination)| bind(&[], key, format!("switch -m {} {}", name, destination))));
+    lines.extend(keys.iter()
+        .map(|(key, destination)| bind(&[&options.move_modifier], key, format!("move -m {} {}", name, destination))));
+    lines.extend(keys.iter()
+        .map(|(key, destination)| bind(
+            &[&options.move_modifier, &options.focus_modifier],
+            key,
+            format!("move -m {} --focus {}", name, destination),
+        )));
+    if group.monitors.len() > 1 {
+        lines.push(bind(&[], &options.swap_key, format!("swap-monitors -m {} 0 1", name)));
+    }
+    if options.assign_outputs {
+        for (monitor_index, (resolved, spec)) in group.resolved_monitors.iter().zip(group.monitor_specs.iter()).enumerate() {
+            let Some(monitor) = resolved else {
+                eprintln!(
+                    "warning: monitor {} of monitor group {} matches no active output, its workspaces are not assigned",
+                    spec, name,
+                );
+                continue;
+            };
+            lines.extend(indices.iter().map(|index| format!(
+                "workspace {} output \"{}\"",
+                WorkspaceId::new(name, monitor_index, *index),
+                monitor,
+            )));
+        }
+    }
+    lines
+}
+
+/// Quote a command argument for the shell that sway runs `exec` commands with
+pub fn shell_quote(argument: &str) -> String {
+    format!("'{}'", argument.replace('\'', "'\\''"))
+}
+
+/// Format sway config lines that start `mumowrk` with sway and bind keys for switching
+/// between workspace groups of every monitor group, moving containers between them and
+/// swapping workspaces between monitors. `command` is used to run `mumowrk`.
+pub fn format_sway_bindings(config: &Config, command: &str, options: &BindingOptions) -> Result<String, String> {
+    let group_modifiers = get_group_modifiers(config, options)?;
+    let mut lines = vec![
+        "# Generated by `mumowrk config sway-bindings`, include this file in the sway config".to_string(),
+        format!("set $mumowrk {}", command),
+        String::new(),
+        "exec $mumowrk init --startup".to_string(),
+    ];
+    for (group, group_modifier) in config.groups.iter().zip(group_modifiers) {
+        lines.push(String::new());
+        lines.extend(format_group_bindings(group, &group_modifier, options));
+    }
+    Ok(lines.join("\n") + "\n")
+}
@@ -1,29 +1,234 @@
-use std::fs;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 
+use itertools::Itertools;
+use schemars::JsonSchema;
 use serde::{Serialize, Deserialize};
-use serde_yml;
+use swayipc::Output;
 
-use super::monitor_group::MonitorGroup;
+use crate::sway::utils::get_output_positions;
+use super::monitor_group::{FallbackPolicy, MonitorGroup};
+use super::profile::Profile;
+use super::settings::GroupSettings;
+use super::source::{expand_path, parse_file, read_file, ConfigSource};
+use super::validation::{validate, ConfigError};
 
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Layout of the container that wraps containers merged from another workspace
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeLayout {
+    Tabbed,
+    Stacked,
+}
+
+impl MergeLayout {
+    /// Name of the layout in sway commands
+    pub fn sway_layout(&self) -> &'static str {
+        match self {
+            MergeLayout::Tabbed => "tabbed",
+            MergeLayout::Stacked => "stacking",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
 pub struct Config {
+    /// Other config files merged under this one, relative to this file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    /// Monitor groups used when no profile is selected
+    #[serde(default)]
     pub groups: Vec<MonitorGroup>,
+    /// Alternative sets of monitor groups selected by the active outputs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<Profile>,
+    /// Settings for all monitor groups, monitor groups can override them
+    #[serde(default, skip_serializing_if = "GroupSettings::is_empty")]
+    pub settings: GroupSettings,
+    /// Name of the selected profile whose groups are in `groups`
+    #[serde(skip)]
+    pub profile: Option<String>,
+    /// Renumber workspace groups to a contiguous range when some of them disappear
+    #[serde(default)]
+    pub auto_compact: bool,
+    /// Number of snapshots of the tree to keep (at least 1)
+    #[serde(default = "default_snapshot_retention")]
+    #[schemars(range(min = 1))]
+    pub snapshot_retention: usize,
+    /// Wrap tiled containers merged from another workspace into a single container
+    /// with this layout to keep them recognizable (default: don't wrap them)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merge_layout: Option<MergeLayout>,
+    /// Paths of the file the config was loaded from and of all its includes
+    #[serde(skip)]
+    pub files: Vec<PathBuf>,
+}
+
+fn default_snapshot_retention() -> usize {
+    10
 }
 
 impl Config {
-    pub fn load(path: &str) -> Self {
-        // Load the configuration from a file
-        let expanded_path = shellexpand::full(path).expect("Failed to expand path");
-        let config_str = fs::read_to_string(expanded_path.into_owned()).expect("Failed to read config file");
-        serde_yml::from_str(&config_str).expect("Failed to parse the config file")
+    /// Load the configuration from a file and validate it
+    pub fn load(path: &str) -> Result<Self, Vec<ConfigError>> {
+        let (config, sources) = Self::read(path).map_err(|error| vec![error])?;
+        let errors = validate(&config, &sources);
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Load and validate the configuration, select the profile and resolve
+    /// and order the monitors for the active outputs
+    pub fn load_for_outputs(path: &str, profile: Option<&str>, active_outputs: &[Output]) -> Result<Self, String> {
+        let mut config = Self::load(path).map_err(|errors| {
+            format!("Invalid config {}:\n{}", path, errors.iter().map(|error| format!("  {}", error)).join("\n"))
+        })?;
+        config.select_profile(profile, active_outputs)?;
+        config.resolve_monitors(active_outputs);
+        config.apply_default_settings();
+        config.apply_monitor_order(&get_output_positions(active_outputs));
+        Ok(config)
+    }
+
+    /// Read and parse the configuration with its includes without validating it.
+    /// The path can contain variables and `~`, the format is given by the extension
+    /// (`.toml`, `.json`, YAML otherwise). Return the read files too, in the order
+    /// they were merged, the file itself being the last one.
+    pub fn read(path: &str) -> Result<(Self, Vec<ConfigSource>), ConfigError> {
+        let path = expand_path(path)?;
+        let source = read_file(&path)?;
+        let mut sources = Vec::new();
+        let mut config: Config = parse_file(&path, &source, &mut sources)?;
+        config.files = sources.iter().map(|source| source.path.clone()).collect();
+        Ok((config, sources))
+    }
+
+    /// Use the groups of the profile with the given name, or of the first profile matching
+    /// the active outputs if `name` is `None`. The top level groups are kept if no profile
+    /// matches.
+    pub fn select_profile(&mut self, name: Option<&str>, active_outputs: &[Output]) -> Result<(), String> {
+        let profile = match name {
+            Some(name) => self.profiles.iter()
+                .find(|profile| profile.name == name)
+                .ok_or(format!("Profile {} is not configured", name))?,
+            None => match self.profiles.iter().find(|profile| profile.matches(active_outputs)) {
+                Some(profile) => profile,
+                None if self.groups.is_empty() => return Err("No profile matches the active outputs".to_string()),
+                None => return Ok(()),
+            },
+        };
+        self.groups = profile.groups.clone();
+        self.profile = Some(profile.name.clone());
+        Ok(())
+    }
+
+    /// Resolve monitors of all groups to the names of the active outputs they match.
+    /// Every entry resolves to at most one output, exact entries take their outputs first
+    /// and patterns take the first unclaimed matching output sorted by name.
+    /// Entries without a matching active output are kept as they are written.
+    pub fn resolve_monitors(&mut self, active_outputs: &[Output]) {
+        let sorted_outputs: Vec<&Output> = active_outputs.iter()
+            .sorted_by(|a, b| a.name.cmp(&b.name))
+            .collect();
+        let mut claimed: HashSet<&str> = sorted_outputs.iter()
+            .filter(|output| self.groups.iter()
+                .flat_map(|group| group.monitor_specs.iter())
+                .any(|spec| spec.is_exact() && spec.matches(output)))
+            .map(|output| output.name.as_str())
+            .collect();
+        for group in self.groups.iter_mut() {
+            group.resolved_monitors = group.monitor_specs.iter()
+                .map(|spec| {
+                    if spec.is_exact() {
+                        return Some(spec.to_string());
+                    }
+                    let output = sorted_outputs.iter()
+                        .find(|output| !claimed.contains(output.name.as_str()) && spec.matches(output))?;
+                    claimed.insert(&output.name);
+                    Some(output.name.clone())
+                })
+                .collect();
+            group.monitors = group.resolved_monitors.iter()
+                .zip(group.monitor_specs.iter())
+                .map(|(resolved, spec)| resolved.clone().unwrap_or_else(|| spec.to_string()))
+                .collect();
+        }
+    }
+
+    /// Fill in settings of all groups that they do not override from the top level settings
+    pub fn apply_default_settings(&mut self) {
+        for group in self.groups.iter_mut() {
+            group.settings.apply_defaults(&self.settings);
+        }
+    }
+
+    /// Monitor group names with their resolved monitors,
+    /// to find out whether a different config assigns monitors differently
+    pub fn get_membership(&self) -> Vec<(&str, &[String])> {
+        self.groups.iter()
+            .map(|group| (group.name.as_str(), group.monitors.as_slice()))
+            .collect()
+    }
+
+    /// Apply monitor ordering of all groups given the positions of active outputs
+    pub fn apply_monitor_order(&mut self, positions: &HashMap<String, (i32, i32)>) {
+        for group in self.groups.iter_mut() {
+            group.apply_monitor_order(positions);
+        }
     }
 
     pub fn get_primary_group(&self) -> &MonitorGroup {
         &self.groups[0]
     }
 
+    /// The first monitor group with an active monitor and the index of its main monitor,
+    /// the default target of commands. Return `None` if no configured monitor is active.
+    pub fn get_primary_monitor(&self, active_monitors: &HashSet<String>) -> Option<(&MonitorGroup, usize)> {
+        self.groups.iter()
+            .find_map(|group| group.get_main_monitor_index(active_monitors).map(|index| (group, index)))
+    }
+
     pub fn get_group(&self, name: &str) -> Option<&MonitorGroup> {
         self.groups.iter().find(|group| group.name == name)
     }
+
+    /// Return the output where workspaces of a monitor group without active monitors
+    /// end up according to its fallback policy: the output they are parked on or, for
+    /// `merge_into`, the main monitor of the target group the containers were merged into.
+    /// Falls back to the first active monitor of any group if the configured target is
+    /// not active. Return `None` for the `leave` policy, whose workspaces are not moved.
+    pub fn get_fallback_monitor(&self, monitor_group: &MonitorGroup, active_monitors: &HashSet<String>) -> Option<String> {
+        let configured_monitor = match &monitor_group.fallback {
+            Some(FallbackPolicy::Leave) => return None,
+            Some(FallbackPolicy::MergeInto { .. }) => self.get_merge_target(monitor_group, active_monitors)
+                .map(|(group, main_monitor_index)| &group.monitors[main_monitor_index]),
+            Some(FallbackPolicy::MonitorGroup { monitor_group: group_name }) => self.get_group(group_name)
+                .and_then(|group| group.monitors.iter().find(|monitor| active_monitors.contains(*monitor))),
+            Some(FallbackPolicy::Output { output }) => Some(output).filter(|output| active_monitors.contains(*output)),
+            None => None,
+        };
+        configured_monitor.or_else(|| self.get_first_active_monitor(active_monitors)).cloned()
+    }
+
+    /// The monitor group and the index of its main monitor that the containers of a monitor
+    /// group with the `merge_into` fallback policy are merged into.
+    /// Return `None` if the policy is different or the target group has no active monitors.
+    pub fn get_merge_target(&self, monitor_group: &MonitorGroup, active_monitors: &HashSet<String>) -> Option<(&MonitorGroup, usize)> {
+        let Some(FallbackPolicy::MergeInto { monitor_group: group_name, .. }) = &monitor_group.fallback else {
+            return None;
+        };
+        let group = self.get_group(group_name)?;
+        group.get_main_monitor_index(active_monitors).map(|index| (group, index))
+    }
+
+    /// The first active monitor of any monitor group
+    pub fn get_first_active_monitor(&self, active_monitors: &HashSet<String>) -> Option<&String> {
+        self.groups.iter()
+            .flat_map(|group| group.monitors.iter())
+            .find(|monitor| active_monitors.contains(*monitor))
+    }
 }
@@ -0,0 +1,122 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use itertools::Itertools;
+use regex::Regex;
+use swayipc::Output;
+
+use crate::sway::utils::get_output_descriptor;
+
+
+/// Output name prefixes of built-in panels
+const BUILT_IN_PREFIXES: [&str; 3] = ["eDP", "LVDS", "DSI"];
+
+fn is_built_in(output: &Output) -> bool {
+    BUILT_IN_PREFIXES.iter().any(|prefix| output.name.starts_with(prefix))
+}
+
+/// Propose monitor groups for the outputs: external monitors in `G1` and built-in
+/// panels in `G2` (or in `G1` if there are no external monitors), ordered by position
+fn propose_groups(outputs: &[Output]) -> Vec<(String, Vec<&Output>)> {
+    let (built_in, external): (Vec<&Output>, Vec<&Output>) = outputs.iter()
+        .sorted_by_key(|output| (output.rect.x, output.rect.y))
+        .partition(|output| is_built_in(output));
+    [external, built_in].into_iter()
+        .filter(|outputs| !outputs.is_empty())
+        .enumerate()
+        .map(|(index, outputs)| (format!("G{}", index + 1), outputs))
+        .collect()
+}
+
+/// Ask for the monitor group of every output, the proposed group is the default
+fn prompt_groups(proposed: Vec<(String, Vec<&Output>)>) -> Result<Vec<(String, Vec<&Output>)>, String> {
+    let valid_name = Regex::new(r"^\w+$").unwrap();
+    let mut groups: Vec<(String, Vec<&Output>)> = Vec::new();
+    for (proposed_name, output) in proposed.iter()
+        .flat_map(|(name, outputs)| outputs.iter().map(move |output| (name, *output)))
+    {
+        let name = loop {
+            eprint!(
+                "Monitor group for {} ({}) at {},{} [{}]: ",
+                output.name, get_output_descriptor(output), output.rect.x, output.rect.y, proposed_name,
+            );
+            io::stderr().flush().map_err(|error| format!("Failed to flush the prompt: {}", error))?;
+            let mut answer = String::new();
+            io::stdin().read_line(&mut answer).map_err(|error| format!("Failed to read the answer: {}", error))?;
+            let answer = answer.trim();
+            if answer.is_empty() {
+                break proposed_name.clone();
+            }
+            if valid_name.is_match(answer) {
+                break answer.to_string();
+            }
+            eprintln!("Monitor group names can only contain letters, digits and underscores");
+        };
+        match groups.iter_mut().find(|(group_name, _)| *group_name == name) {
+            Some((_, outputs)) => outputs.push(output),
+            None => groups.push((name, vec![output])),
+        }
+    }
+    Ok(groups)
+}
+
+/// Write the monitor groups as a commented YAML config
+fn format_config(groups: &[(String, Vec<&Output>)]) -> String {
+    let quote = |text: &str| serde_json::to_string(text).expect("Failed to quote a string");
+    let mut text = String::from(
+        "# Generated by `mumowrk config generate` from the active outputs,\n\
+         # see config.example.yml for all options\n\
+         \n\
+         # the first group is the primary group, the default one to target when switching\n\
+         groups:\n",
+    );
+    for (name, outputs) in groups {
+        text.push_str(&format!("- name: {}\n", quote(name)));
+        text.push_str("  # the first active monitor is the primary monitor of the group\n");
+        text.push_str("  monitors:\n");
+        for output in outputs {
+            text.push_str(&format!(
+                "    - {}  # {} at {},{}\n",
+                quote(&get_output_descriptor(output)), output.name, output.rect.x, output.rect.y,
+            ));
+        }
+    }
+    text
+}
+
+/// Generate a config with monitor groups for the outputs and write it to `path`
+/// (`-` for the standard output). The groups are asked for if `interactive` is set.
+/// An existing file is only overwritten with `force`.
+pub fn generate_config(outputs: &[Output], path: &str, interactive: bool, force: bool) -> Result<(), String> {
+    if outputs.is_empty() {
+        return Err("No active outputs".to_string());
+    }
+    let mut groups = propose_groups(outputs);
+    if interactive {
+        groups = prompt_groups(groups)?;
+    }
+    let text = format_config(&groups);
+    if path == "-" {
+        print!("{}", text);
+        return Ok(());
+    }
+
+    let expanded_path = shellexpand::full(path)
+        .map_err(|error| format!("Failed to expand path {}: {}", path, error))?;
+    let file_path = Path::new(expanded_path.as_ref());
+    if file_path.extension().is_some_and(|extension| extension == "toml" || extension == "json") {
+        return Err(format!("Generated configs are YAML, {} would be read as another format", file_path.display()));
+    }
+    if file_path.exists() && !force {
+        return Err(format!("Config file {} already exists, use --force to overwrite it", file_path.display()));
+    }
+    if let Some(directory) = file_path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(directory)
+            .map_err(|error| format!("Failed to create {}: {}", directory.display(), error))?;
+    }
+    fs::write(file_path, text)
+        .map_err(|error| format!("Failed to write {}: {}", file_path.display(), error))?;
+    println!("Config written to {}", file_path.display());
+    Ok(())
+}
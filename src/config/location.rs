@@ -0,0 +1,178 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use serde_yml::libyml::parser::{Event, Parser};
+use toml::de::{DeTable, DeValue};
+
+use super::source::{line_at, ConfigFormat, ConfigSource};
+use super::validation::ConfigError;
+
+
+/// Key of a map or index in a list on the way from the top of a config file to a value
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+impl From<&str> for PathSegment {
+    fn from(key: &str) -> Self {
+        PathSegment::Key(key.to_string())
+    }
+}
+
+impl From<usize> for PathSegment {
+    fn from(index: usize) -> Self {
+        PathSegment::Index(index)
+    }
+}
+
+/// Line (starting at 1) of a value and whether it is a map, which is merged with
+/// the same map of other files instead of replacing it
+#[derive(Debug, Clone, Copy)]
+struct Location {
+    line: usize,
+    is_map: bool,
+}
+
+/// Locations of all values in a config file by their path, as reported by the parser
+#[derive(Debug, Default)]
+struct FileLocations(HashMap<Vec<PathSegment>, Location>);
+
+impl FileLocations {
+    fn parse(source: &ConfigSource) -> Self {
+        let mut locations = FileLocations::default();
+        match ConfigFormat::from_path(&source.path) {
+            ConfigFormat::Toml => {
+                if let Ok(table) = DeTable::parse(&source.text) {
+                    let line = line_at(&source.text, table.span().start);
+                    locations.add_toml_table(&source.text, table.get_ref(), &mut Vec::new(), line);
+                }
+            },
+            // JSON is also parsed as YAML, the syntax of JSON is a subset of it
+            ConfigFormat::Yaml | ConfigFormat::Json => {
+                let mut parser = Parser::new(Cow::Borrowed(source.text.as_bytes()));
+                while let Ok((event, mark)) = parser.parse_next_event() {
+                    match event {
+                        Event::StreamStart | Event::DocumentStart => continue,
+                        // only the first document is read as the config
+                        event => {
+                            locations.add_yaml_node(&mut parser, event, mark.line() as usize + 1, &mut Vec::new());
+                            break;
+                        },
+                    }
+                }
+            },
+        }
+        locations
+    }
+
+    /// Add a YAML node that starts with `event` and all nodes within it.
+    /// Return `None` if the rest of the file cannot be parsed.
+    fn add_yaml_node(&mut self, parser: &mut Parser, event: Event, line: usize, path: &mut Vec<PathSegment>) -> Option<()> {
+        let is_map = matches!(event, Event::MappingStart(_));
+        match event {
+            Event::MappingStart(_) | Event::SequenceStart(_) | Event::Scalar(_) | Event::Alias(_) => {
+                self.0.insert(path.clone(), Location { line, is_map });
+            },
+            _ => return None,
+        }
+        match event {
+            Event::MappingStart(_) => loop {
+                let key = match parser.parse_next_event().ok()?.0 {
+                    Event::MappingEnd => break,
+                    Event::Scalar(scalar) => String::from_utf8_lossy(&scalar.value).into_owned(),
+                    // keys that are not scalars cannot be in the config
+                    _ => return None,
+                };
+                let (value, mark) = parser.parse_next_event().ok()?;
+                path.push(PathSegment::Key(key));
+                self.add_yaml_node(parser, value, mark.line() as usize + 1, path)?;
+                path.pop();
+            },
+            Event::SequenceStart(_) => for index in 0.. {
+                let (item, mark) = parser.parse_next_event().ok()?;
+                if matches!(item, Event::SequenceEnd) {
+                    break;
+                }
+                path.push(PathSegment::Index(index));
+                self.add_yaml_node(parser, item, mark.line() as usize + 1, path)?;
+                path.pop();
+            },
+            _ => {},
+        }
+        Some(())
+    }
+
+    /// Add a TOML table at `line` and all values within it
+    fn add_toml_table(&mut self, source: &str, table: &DeTable, path: &mut Vec<PathSegment>, line: usize) {
+        self.0.insert(path.clone(), Location { line, is_map: true });
+        for (key, value) in table.iter() {
+            path.push(PathSegment::Key(key.get_ref().to_string()));
+            // the line of the key, the value of a table is the whole table
+            self.add_toml_value(source, value.get_ref(), path, line_at(source, key.span().start));
+            path.pop();
+        }
+    }
+
+    fn add_toml_value(&mut self, source: &str, value: &DeValue, path: &mut Vec<PathSegment>, line: usize) {
+        match value {
+            DeValue::Table(table) => self.add_toml_table(source, table, path, line),
+            DeValue::Array(items) => {
+                self.0.insert(path.clone(), Location { line, is_map: false });
+                for (index, item) in items.iter().enumerate() {
+                    path.push(PathSegment::Index(index));
+                    self.add_toml_value(source, item.get_ref(), path, line_at(source, item.span().start));
+                    path.pop();
+                }
+            },
+            _ => {
+                self.0.insert(path.clone(), Location { line, is_map: false });
+            },
+        }
+    }
+
+    /// Whether the value at `path` comes from this file after merging it on top of the
+    /// files with lower precedence: either this file has it or a value around it that
+    /// is not a map, which replaces the whole value of the files below
+    fn provides(&self, path: &[PathSegment]) -> bool {
+        self.0.contains_key(path)
+            || (0..path.len()).any(|length| self.0.get(&path[..length]).is_some_and(|location| !location.is_map))
+    }
+}
+
+/// Locations of values in the config file and its includes
+#[derive(Debug)]
+pub(super) struct Locations {
+    /// Files in the order of their precedence, the config file itself first without a path
+    files: Vec<(Option<PathBuf>, FileLocations)>,
+}
+
+impl Locations {
+    /// `sources` are in the order they were merged, the config file itself being the last one
+    pub fn new(sources: &[ConfigSource]) -> Self {
+        let files = sources.iter()
+            .rev()
+            .enumerate()
+            .map(|(index, source)| ((index > 0).then(|| source.path.clone()), FileLocations::parse(source)))
+            .collect();
+        Self { files }
+    }
+
+    /// Create an error on the line of the value at `path` in the file that value was merged
+    /// from. Values that are not in any file, like defaults, get the line of the nearest
+    /// value around them.
+    pub fn error(&self, path: &[PathSegment], message: impl Into<String>) -> ConfigError {
+        let location = (0..=path.len()).rev()
+            .map(|length| &path[..length])
+            .find_map(|path| {
+                let (file, locations) = self.files.iter().find(|(_, locations)| locations.provides(path))?;
+                Some((file, locations.0.get(path)?.line))
+            });
+        match location {
+            Some((file, line)) => ConfigError { file: file.clone(), line: Some(line), message: message.into() },
+            None => ConfigError::new(None, message),
+        }
+    }
+}
@@ -1,5 +1,24 @@
+mod bindings;
+#[allow(clippy::module_inception)]
 mod config;
+mod generate;
+mod location;
 mod monitor_group;
+mod monitor_spec;
+mod profile;
+mod schema;
+mod settings;
+mod source;
+mod startup;
+mod validation;
+mod watch;
 
-pub use config::Config;
-pub use monitor_group::{FIRST_WORKSPACE_GROUP, MonitorGroup};
+pub use bindings::{format_sway_bindings, shell_quote, BindingOptions};
+pub use config::{Config, MergeLayout};
+pub use generate::generate_config;
+pub use monitor_group::{FIRST_WORKSPACE_GROUP, FallbackPolicy, MonitorGroup, MonitorOrder};
+pub use schema::get_config_schema;
+pub use settings::{NotificationVerbosity, StatusFormat};
+pub use source::{default_config_path, find_config_path};
+pub use validation::check_config;
+pub use watch::watch_file;
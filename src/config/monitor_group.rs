@@ -1,33 +1,148 @@
+use schemars::JsonSchema;
 use serde::{Serialize, Deserialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+
+use super::monitor_spec::MonitorSpec;
+use super::settings::{GroupSettings, NotificationVerbosity, StatusFormat};
+use super::startup::StartupCommand;
 
 
 /// The index of the origin workspace group
 pub const FIRST_WORKSPACE_GROUP: i32 = 1;
 
+/// What to do with workspaces of a monitor group that has no active monitors
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(tag = "policy", rename_all = "snake_case")]
+pub enum FallbackPolicy {
+    /// Park workspaces on the main monitor of another monitor group
+    MonitorGroup {
+        monitor_group: String,
+    },
+    /// Park workspaces on a specific output
+    Output {
+        output: String,
+    },
+    /// Move all containers into a workspace group of another monitor group
+    MergeInto {
+        monitor_group: String,
+        index: i32,
+    },
+    /// Leave the workspaces as they are without renaming them. Sway cannot hide workspaces,
+    /// they stay visible on whichever output sway moved them to when their monitor was
+    /// disconnected and switching to the group is not possible until one of its monitors
+    /// is active again.
+    Leave,
+}
+
+/// How indices are assigned to monitors within a group
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MonitorOrder {
+    /// In the order of the `monitors` list
+    #[default]
+    List,
+    /// Active monitors by the position of their outputs, left to right and top to bottom
+    Position,
+}
+
 /// Group of monitors that should share a workspace group
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct MonitorGroup {
     pub name: String,
+    /// Monitors as configured, they may be patterns
+    #[serde(rename = "monitors")]
+    pub monitor_specs: Vec<MonitorSpec>,
+    /// Names or descriptors of the monitors resolved from `monitor_specs`,
+    /// the index in this list is the monitor index
+    #[serde(skip)]
     pub monitors: Vec<String>,
+    /// Output every entry of `monitor_specs` resolved to, `None` for patterns
+    /// that matched no active output and are kept as they are written in `monitors`
+    #[serde(skip)]
+    pub resolved_monitors: Vec<Option<String>>,
+    /// Policy for when none of the monitors are active
+    /// (default: park workspaces on the first active monitor of any group)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<FallbackPolicy>,
+    /// How monitor indices are assigned (default: list order)
+    #[serde(default)]
+    pub order: MonitorOrder,
+    /// Overrides of the top level settings for this group
+    #[serde(default, skip_serializing_if = "GroupSettings::is_empty")]
+    pub settings: GroupSettings,
+    /// Commands started by `init` on workspaces of this group
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub startup: Vec<StartupCommand>,
 }
 
 
 impl MonitorGroup {
     pub fn get_name(&self) -> &str {
-        return &self.name;
+        &self.name
     }
 
     /// The first active monitor in the list of monitors for the group
-    /// is considered the main monitor. Return its index.
-    pub fn get_main_monitor_index(&self, active_monitors: &HashSet<String>) -> usize {
+    /// is considered the main monitor. Return its index or `None` if the group
+    /// has no active monitors.
+    pub fn get_main_monitor_index(&self, active_monitors: &HashSet<String>) -> Option<usize> {
         self.monitors.iter()
             .position(|monitor| active_monitors.contains(monitor))
-            .expect("No active monitors in the primary monitor group")
+    }
+
+    /// Whether any of the monitors in the group are active
+    pub fn has_active_monitor(&self, active_monitors: &HashSet<String>) -> bool {
+        self.monitors.iter().any(|monitor| active_monitors.contains(monitor))
+    }
+
+    /// Reorder the active monitors by their position if the group is ordered by position.
+    /// `positions` are the output positions by both output name and descriptor.
+    /// Inactive monitors keep their index.
+    pub fn apply_monitor_order(&mut self, positions: &HashMap<String, (i32, i32)>) {
+        if self.order != MonitorOrder::Position {
+            return;
+        }
+        let slots: Vec<usize> = self.monitors.iter()
+            .enumerate()
+            .filter(|(_, monitor)| positions.contains_key(*monitor))
+            .map(|(index, _)| index)
+            .collect();
+        let mut active: Vec<String> = slots.iter()
+            .map(|index| self.monitors[*index].clone())
+            .collect();
+        active.sort_by_key(|monitor| positions[monitor]);
+        for (index, monitor) in slots.into_iter().zip(active) {
+            self.monitors[index] = monitor;
+        }
+    }
+
+    /// Keep a workspace group index within `first_index` and `last_index`,
+    /// wrapping it around if `wrap` is enabled and both are set
+    pub fn bound_index(&self, index: i32) -> i32 {
+        let settings = &self.settings;
+        match (settings.first_index, settings.last_index) {
+            (Some(first), Some(last)) if settings.wrap.unwrap_or_default() && last >= first =>
+                first + (index - first).rem_euclid(last - first + 1),
+            (first, last) => {
+                let index = first.map_or(index, |first| index.max(first));
+                last.map_or(index, |last| index.min(last))
+            },
+        }
+    }
+
+    pub fn get_notify(&self) -> NotificationVerbosity {
+        self.settings.notify.clone().unwrap_or_default()
+    }
+
+    pub fn get_status_format(&self) -> StatusFormat {
+        self.settings.status.unwrap_or_default()
+    }
+
+    pub fn is_linked(&self) -> bool {
+        self.settings.linked.unwrap_or_default()
     }
 
     pub fn get_monitor_index(&self, monitor_name: &str) -> Option<usize> {
         self.monitors.iter()
             .position(|monitor| monitor == monitor_name)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,160 @@
+use std::fmt;
+
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Serialize, Deserialize};
+use swayipc::Output;
+
+use crate::sway::utils::get_output_descriptor;
+
+
+/// Prefix of patterns that are regular expressions instead of globs
+const REGEX_PREFIX: &str = "re:";
+
+/// Monitor entry of a monitor group in the config
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum MonitorSpec {
+    /// Output name or descriptor (make+model+serial), either exact,
+    /// a glob pattern (`*`, `?`) or a regular expression prefixed with `re:`
+    Pattern(String),
+    /// Output properties matched separately, each one a pattern like above.
+    /// Missing properties match any output.
+    Properties {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        make: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        model: Option<String>,
+        /// Matched against the serial as reported by sway, empty if unknown
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        serial: Option<String>,
+    },
+}
+
+impl MonitorSpec {
+    /// Whether this is a plain output name or descriptor
+    pub fn is_exact(&self) -> bool {
+        match self {
+            MonitorSpec::Pattern(pattern) => !is_pattern(pattern),
+            MonitorSpec::Properties { .. } => false,
+        }
+    }
+
+    pub fn matches(&self, output: &Output) -> bool {
+        match self {
+            MonitorSpec::Pattern(pattern) => pattern_matches(pattern, &output.name)
+                || pattern_matches(pattern, &get_output_descriptor(output)),
+            MonitorSpec::Properties { name, make, model, serial } => [
+                (name, &output.name),
+                (make, &output.make),
+                (model, &output.model),
+                (serial, &output.serial),
+            ].into_iter().all(|(pattern, value)| {
+                pattern.as_ref().is_none_or(|pattern| pattern_matches(pattern, value))
+            }),
+        }
+    }
+
+    /// Patterns of this entry that are invalid regular expressions, with the error
+    pub fn invalid_patterns(&self) -> Vec<(String, regex::Error)> {
+        let patterns = match self {
+            MonitorSpec::Pattern(pattern) => vec![pattern],
+            MonitorSpec::Properties { name, make, model, serial } => [name, make, model, serial]
+                .into_iter()
+                .flatten()
+                .collect(),
+        };
+        patterns.into_iter()
+            .filter_map(|pattern| pattern_to_regex(pattern).err().map(|error| (pattern.clone(), error)))
+            .collect()
+    }
+}
+
+impl fmt::Display for MonitorSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MonitorSpec::Pattern(pattern) => write!(f, "{}", pattern),
+            MonitorSpec::Properties { name, make, model, serial } => {
+                let properties = [("name", name), ("make", make), ("model", model), ("serial", serial)]
+                    .into_iter()
+                    .filter_map(|(key, pattern)| pattern.as_ref().map(|pattern| format!("{}={}", key, pattern)))
+                    .collect::<Vec<_>>();
+                write!(f, "{{{}}}", properties.join(" "))
+            },
+        }
+    }
+}
+
+fn is_pattern(pattern: &str) -> bool {
+    pattern.starts_with(REGEX_PREFIX) || pattern.contains(['*', '?'])
+}
+
+/// Translate a glob or a `re:` prefixed pattern to an anchored regular expression
+fn pattern_to_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    match pattern.strip_prefix(REGEX_PREFIX) {
+        Some(expression) => Regex::new(&format!("^(?:{})$", expression)),
+        None => Regex::new(&format!(
+            "^{}$",
+            regex::escape(pattern).replace(r"\*", ".*").replace(r"\?", "."),
+        )),
+    }
+}
+
+fn pattern_matches(pattern: &str, value: &str) -> bool {
+    if !is_pattern(pattern) {
+        return pattern == value;
+    }
+    pattern_to_regex(pattern).is_ok_and(|regex| regex.is_match(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pattern_matches, pattern_to_regex};
+
+    #[test]
+    fn translates_glob_wildcards() {
+        assert_eq!(pattern_to_regex("DP-*").unwrap().as_str(), r"^DP\-.*$");
+        assert_eq!(pattern_to_regex("DP-?").unwrap().as_str(), r"^DP\-.$");
+        assert!(pattern_matches("DP-*", "DP-12"));
+        assert!(pattern_matches("DP-?", "DP-1"));
+        assert!(!pattern_matches("DP-?", "DP-12"));
+    }
+
+    #[test]
+    fn escapes_globs() {
+        assert!(pattern_matches("Dell Inc. (*)", "Dell Inc. (ABC)"));
+        assert!(!pattern_matches("DP.?", "DPX1"));
+        assert!(!pattern_matches("[DH]*", "DP-1"));
+    }
+
+    #[test]
+    fn anchors_globs() {
+        assert!(!pattern_matches("DP-*", "eDP-1"));
+        assert!(!pattern_matches("*-1", "DP-12"));
+    }
+
+    #[test]
+    fn anchors_regular_expressions() {
+        assert!(pattern_matches(r"re:DP-\d", "DP-1"));
+        assert!(!pattern_matches(r"re:DP-\d", "eDP-1"));
+        assert!(!pattern_matches(r"re:DP-\d", "DP-12"));
+        // the whole alternation is anchored, not only its first and last branch
+        assert!(!pattern_matches("re:DP-1|HDMI-A-1", "DP-10"));
+        assert!(!pattern_matches("re:DP-1|HDMI-A-1", "eHDMI-A-1"));
+    }
+
+    #[test]
+    fn rejects_invalid_regular_expressions() {
+        assert!(pattern_to_regex("re:(").is_err());
+        assert!(!pattern_matches("re:(", "("));
+    }
+
+    #[test]
+    fn matches_names_without_wildcards_exactly() {
+        assert!(pattern_matches("Dell Inc. DELL U2720Q 1AB2C3", "Dell Inc. DELL U2720Q 1AB2C3"));
+        assert!(!pattern_matches("DP-1", "DP-12"));
+        assert!(!pattern_matches("DP-1", "eDP-1"));
+    }
+}
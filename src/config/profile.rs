@@ -0,0 +1,39 @@
+use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::{Serialize, Deserialize};
+use swayipc::Output;
+
+use super::monitor_group::MonitorGroup;
+use super::monitor_spec::MonitorSpec;
+
+
+/// Named set of monitor groups used when a specific set of outputs is active
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct Profile {
+    pub name: String,
+    /// Outputs that have to be active, and no other outputs, for the profile
+    /// to be selected (default: all monitors of its groups)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outputs: Option<Vec<MonitorSpec>>,
+    pub groups: Vec<MonitorGroup>,
+}
+
+impl Profile {
+    /// Whether every output of this profile matches a different active output
+    /// and every active output is matched by an output of this profile
+    pub fn matches(&self, active_outputs: &[Output]) -> bool {
+        let specs: Vec<&MonitorSpec> = match &self.outputs {
+            Some(outputs) => outputs.iter().collect(),
+            None => self.groups.iter().flat_map(|group| group.monitor_specs.iter()).collect(),
+        };
+        let mut unmatched: Vec<&Output> = active_outputs.iter().collect();
+        // match the exact outputs first so that patterns do not take them
+        let all_specs_matched = specs.iter()
+            .sorted_by_key(|spec| !spec.is_exact())
+            .all(|spec| {
+                let position = unmatched.iter().position(|output| spec.matches(output));
+                position.map(|position| unmatched.remove(position)).is_some()
+            });
+        all_specs_matched && unmatched.is_empty()
+    }
+}
@@ -0,0 +1,43 @@
+use schemars::{schema_for, Schema};
+
+use super::config::Config;
+
+
+/// JSON Schema of the config file, e.g. for validation and completion in editors
+pub fn get_config_schema() -> Schema {
+    schema_for!(Config)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json::Value;
+
+    use super::get_config_schema;
+
+    const SCHEMA_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/config.schema.json");
+    const EXAMPLE_CONFIG_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/config.example.yml");
+
+    #[test]
+    fn shipped_schema_matches_config() {
+        let schema = serde_json::to_string_pretty(&get_config_schema()).unwrap();
+        let shipped_schema = fs::read_to_string(SCHEMA_PATH).unwrap();
+        assert_eq!(
+            shipped_schema.trim_end(),
+            schema,
+            "config.schema.json is outdated, regenerate it with `mumowrk config schema > config.schema.json`",
+        );
+    }
+
+    #[test]
+    fn example_config_matches_schema() {
+        let schema = serde_json::to_value(get_config_schema()).unwrap();
+        let validator = jsonschema::validator_for(&schema).unwrap();
+        let example: Value = serde_yml::from_str(&fs::read_to_string(EXAMPLE_CONFIG_PATH).unwrap()).unwrap();
+        let errors: Vec<String> = validator.iter_errors(&example)
+            .map(|error| format!("{} at {}", error, error.instance_path()))
+            .collect();
+        assert!(errors.is_empty(), "config.example.yml does not match the schema:\n{}", errors.join("\n"));
+    }
+}
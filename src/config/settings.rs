@@ -0,0 +1,74 @@
+use schemars::JsonSchema;
+use serde::{Serialize, Deserialize};
+
+
+/// Notification sent after switching workspace groups
+#[derive(Debug, clap::ValueEnum, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationVerbosity {
+    #[default]
+    /// Don't show any notification
+    None,
+    /// Show only current workspace group index
+    Index,
+    /// Show the whole state summary
+    Summary,
+}
+
+/// How a monitor group is shown in the printed status
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusFormat {
+    /// All workspace groups
+    #[default]
+    All,
+    /// Only the visible workspace group
+    Current,
+    /// Leave the monitor group out
+    Hidden,
+}
+
+/// Settings of a monitor group. Settings in the top level `settings` of the config
+/// apply to all monitor groups, settings of a monitor group override them.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq)]
+pub struct GroupSettings {
+    /// Lowest workspace group index to switch to (default: unbounded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_index: Option<i32>,
+    /// Highest workspace group index to switch to (default: unbounded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_index: Option<i32>,
+    /// Wrap around when switching past `first_index` or `last_index` (default: false)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wrap: Option<bool>,
+    /// Index of the monitor to focus when switching to the monitor group from another one
+    /// (default: keep the focus where it is)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus_monitor: Option<usize>,
+    /// Notification to send after switching if `--notify` is not given (default: none)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notify: Option<NotificationVerbosity>,
+    /// How the monitor group is shown in the printed status (default: all)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<StatusFormat>,
+    /// Switch together with the other linked monitor groups (default: false)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub linked: Option<bool>,
+}
+
+impl GroupSettings {
+    pub fn is_empty(&self) -> bool {
+        *self == GroupSettings::default()
+    }
+
+    /// Fill in settings that are not set from `defaults`
+    pub fn apply_defaults(&mut self, defaults: &GroupSettings) {
+        self.first_index = self.first_index.or(defaults.first_index);
+        self.last_index = self.last_index.or(defaults.last_index);
+        self.wrap = self.wrap.or(defaults.wrap);
+        self.focus_monitor = self.focus_monitor.or(defaults.focus_monitor);
+        self.notify = self.notify.clone().or(defaults.notify.clone());
+        self.status = self.status.or(defaults.status);
+        self.linked = self.linked.or(defaults.linked);
+    }
+}
@@ -0,0 +1,271 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::validation::ConfigError;
+
+
+/// Environment variable with the path to the config file
+const CONFIG_PATH_VARIABLE: &str = "MUMOWRK_CONFIG";
+
+/// Names of the config file in the `mumowrk` directory of the config directories
+const CONFIG_FILE_NAMES: [&str; 4] = ["config.yml", "config.yaml", "config.toml", "config.json"];
+
+/// Format of a config file, decided by its extension (YAML by default)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum ConfigFormat {
+    Yaml,
+    Toml,
+    Json,
+}
+
+impl ConfigFormat {
+    pub(super) fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => ConfigFormat::Toml,
+            Some("json") => ConfigFormat::Json,
+            _ => ConfigFormat::Yaml,
+        }
+    }
+
+    fn parse<T: DeserializeOwned>(&self, source: &str, path: &Path) -> Result<T, ConfigError> {
+        let error = |line: Option<usize>, error: &dyn std::fmt::Display| ConfigError::new(
+            line,
+            format!("failed to parse {}: {}", path.display(), error),
+        );
+        match self {
+            ConfigFormat::Yaml => serde_yml::from_str(source)
+                .map_err(|e| error(e.location().map(|location| location.line()), &e)),
+            ConfigFormat::Toml => toml::from_str(source)
+                .map_err(|e| error(e.span().map(|span| line_at(source, span.start)), &e.message())),
+            ConfigFormat::Json => serde_json::from_str(source)
+                .map_err(|e| error(Some(e.line()).filter(|line| *line > 0), &e)),
+        }
+    }
+}
+
+/// A config file with its text
+#[derive(Debug, Clone)]
+pub struct ConfigSource {
+    pub path: PathBuf,
+    pub text: String,
+}
+
+/// Line number (starting at 1) of a byte offset in the source
+pub(super) fn line_at(source: &str, offset: usize) -> usize {
+    source[..offset.min(source.len())].matches('\n').count() + 1
+}
+
+/// Find the config file: `$MUMOWRK_CONFIG`, then `mumowrk/config.{yml,yaml,toml,json}`
+/// in `$XDG_CONFIG_HOME` (default `~/.config`) and then in each of `$XDG_CONFIG_DIRS`
+/// (default `/etc/xdg`). Return the searched paths if none of them exists.
+pub fn find_config_path() -> Result<String, Vec<String>> {
+    if let Ok(path) = env::var(CONFIG_PATH_VARIABLE) {
+        return Ok(path);
+    }
+    let config_dirs = env::var("XDG_CONFIG_DIRS")
+        .ok()
+        .filter(|directories| !directories.is_empty())
+        .unwrap_or("/etc/xdg".to_string());
+
+    let candidates: Vec<PathBuf> = get_config_home().into_iter()
+        .chain(config_dirs.split(':').map(str::to_string))
+        .flat_map(|directory| CONFIG_FILE_NAMES.map(|file_name| Path::new(&directory).join("mumowrk").join(file_name)))
+        .collect();
+    candidates.iter()
+        .find(|path| path.is_file())
+        .map(|path| path.to_string_lossy().into_owned())
+        .ok_or_else(|| candidates.iter().map(|path| path.to_string_lossy().into_owned()).collect())
+}
+
+/// Path of the YAML config file in `$XDG_CONFIG_HOME` (default `~/.config`)
+pub fn default_config_path() -> Result<String, String> {
+    let config_home = get_config_home()
+        .ok_or("Neither $XDG_CONFIG_HOME nor $HOME is set, give the path with --output")?;
+    Ok(Path::new(&config_home).join("mumowrk").join(CONFIG_FILE_NAMES[0]).to_string_lossy().into_owned())
+}
+
+fn get_config_home() -> Option<String> {
+    env::var("XDG_CONFIG_HOME")
+        .ok()
+        .filter(|directory| !directory.is_empty())
+        .or_else(|| env::var("HOME").ok().map(|home| format!("{}/.config", home)))
+}
+
+/// Expand variables and `~` in a path
+pub(super) fn expand_path(path: &str) -> Result<PathBuf, ConfigError> {
+    shellexpand::full(path)
+        .map(|expanded| PathBuf::from(expanded.as_ref()))
+        .map_err(|error| ConfigError::new(None, format!("failed to expand path {}: {}", path, error)))
+}
+
+pub(super) fn read_file(path: &Path) -> Result<String, ConfigError> {
+    fs::read_to_string(path)
+        .map_err(|error| ConfigError::new(None, format!("failed to read {}: {}", path.display(), error)))
+}
+
+/// Parse a config file in the format given by its extension. If it has `include`s, the included
+/// files are merged first, in order, and the file itself is merged on top of them.
+/// Every read file is added to `sources` in the order they are merged, the last one
+/// being `path` itself.
+pub(super) fn parse_file<T: DeserializeOwned>(path: &Path, source: &str, sources: &mut Vec<ConfigSource>) -> Result<T, ConfigError> {
+    let format = ConfigFormat::from_path(path);
+    let value: Value = format.parse(source, path)?;
+    if get_includes(&value).is_empty() {
+        sources.push(ConfigSource { path: path.to_path_buf(), text: source.to_string() });
+        // parse directly to keep the lines in errors
+        return format.parse(source, path);
+    }
+    let merged = merge_includes(path, value, sources, &mut vec![path.to_path_buf()])?;
+    sources.push(ConfigSource { path: path.to_path_buf(), text: source.to_string() });
+    serde_json::from_value(merged)
+        .map_err(|error| ConfigError::new(None, format!("invalid config {} with its includes: {}", path.display(), error)))
+}
+
+fn get_includes(value: &Value) -> Vec<String> {
+    value.get("include")
+        .and_then(|include| include.as_array())
+        .map(|include| include.iter().filter_map(|path| path.as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+/// Merge the files included by `value` (which was read from `path`) and then `value` on top.
+/// `including` are the files whose includes are being merged, from the outermost one.
+fn merge_includes(
+    path: &Path,
+    value: Value,
+    sources: &mut Vec<ConfigSource>,
+    including: &mut Vec<PathBuf>,
+) -> Result<Value, ConfigError> {
+    let directory = path.parent().unwrap_or(Path::new("."));
+    let mut merged = Value::Object(Default::default());
+    for include in get_includes(&value) {
+        // relative includes are relative to the including file
+        let include_path = directory.join(expand_path(&include)?);
+        if including.contains(&include_path) || sources.iter().any(|source| source.path == include_path) {
+            return Err(ConfigError::new(None, format!(
+                "{} is included more than once or in a cycle from {}",
+                include_path.display(), path.display(),
+            )));
+        }
+        let source = read_file(&include_path)?;
+        let included: Value = ConfigFormat::from_path(&include_path).parse(&source, &include_path)?;
+        including.push(include_path.clone());
+        let included = merge_includes(&include_path, included, sources, including)?;
+        including.pop();
+        sources.push(ConfigSource { path: include_path, text: source });
+        merge_values(&mut merged, included);
+    }
+    merge_values(&mut merged, value);
+    if let Value::Object(map) = &mut merged {
+        map.remove("include");
+    }
+    Ok(merged)
+}
+
+/// Merge `overrides` into `base`: maps are merged key by key, anything else is replaced
+fn merge_values(base: &mut Value, overrides: Value) {
+    match (base, overrides) {
+        (Value::Object(base), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(base_value) => merge_values(base_value, value),
+                    None => {
+                        base.insert(key, value);
+                    },
+                }
+            }
+        },
+        (base, overrides) => *base = overrides,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use serde_json::{json, Value};
+
+    use super::{merge_values, parse_file, read_file, ConfigError, ConfigSource};
+
+    /// Write config files to a new temporary directory and return it
+    fn write_files(test: &str, files: &[(&str, &str)]) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("mumowrk-{}-{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        for (name, text) in files {
+            fs::write(directory.join(name), text).unwrap();
+        }
+        directory
+    }
+
+    fn parse(path: &Path) -> Result<(Value, Vec<ConfigSource>), ConfigError> {
+        let mut sources = Vec::new();
+        let value = parse_file(path, &read_file(path)?, &mut sources)?;
+        Ok((value, sources))
+    }
+
+    #[test]
+    fn merges_maps_by_key() {
+        let mut base = json!({"settings": {"wrap": true, "first_index": 1}, "auto_compact": false});
+        merge_values(&mut base, json!({"settings": {"first_index": 2}, "merge_layout": "tabbed"}));
+        assert_eq!(base, json!({
+            "settings": {"wrap": true, "first_index": 2},
+            "auto_compact": false,
+            "merge_layout": "tabbed",
+        }));
+    }
+
+    #[test]
+    fn replaces_lists_and_scalars() {
+        let mut base = json!({"groups": [{"name": "A"}, {"name": "B"}], "settings": {"wrap": true}});
+        merge_values(&mut base, json!({"groups": [{"name": "C"}], "settings": 1}));
+        assert_eq!(base, json!({"groups": [{"name": "C"}], "settings": 1}));
+
+        let mut base = json!({"settings": 1});
+        merge_values(&mut base, json!({"settings": {"wrap": true}}));
+        assert_eq!(base, json!({"settings": {"wrap": true}}));
+    }
+
+    #[test]
+    fn merges_the_including_file_over_its_includes() {
+        let directory = write_files("includes", &[
+            ("config.yml", "include: [base.toml]\nsettings:\n  wrap: true\n"),
+            ("base.toml", "auto_compact = true\n[settings]\nwrap = false\nfirst_index = 1\n"),
+        ]);
+        let (value, sources) = parse(&directory.join("config.yml")).unwrap();
+        assert_eq!(value, json!({"auto_compact": true, "settings": {"wrap": true, "first_index": 1}}));
+        let paths: Vec<PathBuf> = sources.into_iter().map(|source| source.path).collect();
+        assert_eq!(paths, [directory.join("base.toml"), directory.join("config.yml")]);
+    }
+
+    #[test]
+    fn rejects_include_cycles() {
+        let directory = write_files("include-cycle", &[
+            ("config.yml", "include: [a.yml]\n"),
+            ("a.yml", "include: [b.yml]\n"),
+            ("b.yml", "include: [a.yml]\n"),
+        ]);
+        let error = parse(&directory.join("config.yml")).unwrap_err();
+        assert!(error.message.contains("a.yml is included more than once or in a cycle"), "{}", error);
+
+        let directory = write_files("include-self", &[("config.yml", "include: [config.yml]\n")]);
+        let error = parse(&directory.join("config.yml")).unwrap_err();
+        assert!(error.message.contains("config.yml is included more than once or in a cycle"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_files_included_twice() {
+        let directory = write_files("include-twice", &[
+            ("config.yml", "include: [a.yml, b.yml]\n"),
+            ("a.yml", "auto_compact: true\n"),
+            ("b.yml", "include: [a.yml]\n"),
+        ]);
+        let error = parse(&directory.join("config.yml")).unwrap_err();
+        assert!(error.message.contains("a.yml is included more than once or in a cycle"), "{}", error);
+    }
+}
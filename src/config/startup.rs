@@ -0,0 +1,26 @@
+use schemars::JsonSchema;
+use serde::{Serialize, Deserialize};
+
+use super::monitor_group::FIRST_WORKSPACE_GROUP;
+
+
+/// Command that `init --startup` starts on a workspace of a monitor group
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct StartupCommand {
+    /// Index of the workspace group (default: the first one)
+    #[serde(default = "default_index")]
+    pub index: i32,
+    /// Index of the monitor within the monitor group (default: 0)
+    #[serde(default)]
+    pub monitor: usize,
+    /// Command executed with sway `exec` while the workspace is focused
+    pub command: String,
+    /// Wait for a new window with this app_id and move it to the workspace,
+    /// for applications whose windows do not open on the workspace by themselves
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_id: Option<String>,
+}
+
+fn default_index() -> i32 {
+    FIRST_WORKSPACE_GROUP
+}
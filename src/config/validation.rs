@@ -0,0 +1,274 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::PathBuf;
+
+use itertools::Itertools;
+use regex::Regex;
+use swayipc::Output;
+
+use crate::sway::utils::get_output_descriptor;
+use super::config::Config;
+use super::location::{Locations, PathSegment};
+use super::monitor_group::{FallbackPolicy, MonitorGroup};
+use super::settings::GroupSettings;
+use super::source::ConfigSource;
+
+
+/// A problem found in the configuration file, optionally with the line it is on
+/// and the included file that line is in
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    pub file: Option<PathBuf>,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl ConfigError {
+    pub fn new(line: Option<usize>, message: impl Into<String>) -> Self {
+        Self { file: None, line, message: message.into() }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => write!(f, "{}:{}: {}", file.display(), line, self.message),
+            (None, Some(line)) => write!(f, "line {}: {}", line, self.message),
+            (_, None) => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Check the configuration for problems that would only surface later as panics
+/// or surprising behaviour. `sources` are the configuration file and its includes
+/// in the order they were merged, used to find the lines of the problems.
+/// Lines in included files are reported together with the file.
+pub fn validate(config: &Config, sources: &[ConfigSource]) -> Vec<ConfigError> {
+    let locations = Locations::new(sources);
+    let mut errors = Vec::new();
+    if config.groups.is_empty() && config.profiles.is_empty() {
+        errors.push(ConfigError::new(None, "no monitor groups or profiles configured"));
+    }
+
+    if config.snapshot_retention == 0 {
+        errors.push(locations.error(&["snapshot_retention".into()], "snapshot_retention has to be at least 1"));
+    }
+    validate_groups(&config.groups, &config.settings, &locations, &["groups".into()], &mut errors);
+
+    let mut profile_names = HashSet::new();
+    for (profile_index, profile) in config.profiles.iter().enumerate() {
+        let profile_path: Vec<PathSegment> = vec!["profiles".into(), profile_index.into()];
+        let name_path = [profile_path.as_slice(), &["name".into()]].concat();
+        if !profile_names.insert(&profile.name) {
+            errors.push(locations.error(&name_path, format!("duplicate profile name {}", profile.name)));
+        }
+        if profile.groups.is_empty() {
+            errors.push(locations.error(&name_path, format!("profile {} has no monitor groups", profile.name)));
+        }
+        let groups_path = [profile_path.as_slice(), &["groups".into()]].concat();
+        validate_groups(&profile.groups, &config.settings, &locations, &groups_path, &mut errors);
+    }
+    errors
+}
+
+/// Check a set of monitor groups that are used together (top level or of a profile),
+/// `groups_path` is where they are in the config
+fn validate_groups(
+    groups: &[MonitorGroup],
+    default_settings: &GroupSettings,
+    locations: &Locations,
+    groups_path: &[PathSegment],
+    errors: &mut Vec<ConfigError>,
+) {
+    // monitor group names are a part of workspace names, see `WorkspaceId`
+    let group_name_re = Regex::new(r"^\w+$").unwrap();
+    let mut names = HashSet::new();
+    // index of the monitor group of each monitor seen so far
+    let mut monitor_groups: HashMap<String, usize> = HashMap::new();
+
+    for (group_index, group) in groups.iter().enumerate() {
+        let group_path = [groups_path, &[group_index.into()]].concat();
+        // path of a value within the monitor group
+        let at = |segments: &[PathSegment]| [group_path.as_slice(), segments].concat();
+        // path of a setting, either of the monitor group or the top level one it falls back to
+        let setting_path = |key: &str, overridden: bool| match overridden {
+            true => at(&["settings".into(), key.into()]),
+            false => vec!["settings".into(), key.into()],
+        };
+
+        let name_path = at(&["name".into()]);
+        if !names.insert(&group.name) {
+            errors.push(locations.error(&name_path, format!("duplicate monitor group name {}", group.name)));
+        }
+        if !group_name_re.is_match(&group.name) {
+            errors.push(locations.error(&name_path, format!(
+                "monitor group name '{}' can only contain letters, digits and underscores",
+                group.name,
+            )));
+        }
+        if group.monitor_specs.is_empty() {
+            errors.push(locations.error(&name_path, format!("monitor group {} has no monitors", group.name)));
+        }
+
+        for (monitor_index, spec) in group.monitor_specs.iter().enumerate() {
+            let monitor = spec.to_string();
+            let path = at(&["monitors".into(), monitor_index.into()]);
+            for (pattern, error) in spec.invalid_patterns() {
+                errors.push(locations.error(&path, format!("invalid pattern {}: {}", pattern, error)));
+            }
+            match monitor_groups.insert(monitor.clone(), group_index) {
+                Some(other_index) if other_index == group_index => errors.push(locations.error(&path, format!(
+                    "monitor {} is listed more than once in monitor group {}",
+                    monitor, group.name,
+                ))),
+                Some(other_index) => errors.push(locations.error(&path, format!(
+                    "monitor {} is in both monitor groups {} and {}",
+                    monitor, groups[other_index].name, group.name,
+                ))),
+                None => {},
+            }
+        }
+
+        let mut settings = group.settings.clone();
+        settings.apply_defaults(default_settings);
+        if let (Some(first), Some(last)) = (settings.first_index, settings.last_index) {
+            if first > last {
+                let path = setting_path("last_index", group.settings.last_index.is_some());
+                errors.push(locations.error(&path, format!(
+                    "last_index {} of monitor group {} is lower than its first_index {}",
+                    last, group.name, first,
+                )));
+            }
+        }
+        if let Some(index) = settings.focus_monitor.filter(|index| *index >= group.monitor_specs.len()) {
+            let path = setting_path("focus_monitor", group.settings.focus_monitor.is_some());
+            errors.push(locations.error(&path, format!(
+                "focus_monitor {} of monitor group {} is out of range, it has {} monitors",
+                index, group.name, group.monitor_specs.len(),
+            )));
+        }
+        for (startup_index, startup) in group.startup.iter().enumerate() {
+            let path = |key: &str| at(&["startup".into(), startup_index.into(), key.into()]);
+            if startup.monitor >= group.monitor_specs.len() {
+                errors.push(locations.error(&path("monitor"), format!(
+                    "startup monitor {} of monitor group {} is out of range, it has {} monitors",
+                    startup.monitor, group.name, group.monitor_specs.len(),
+                )));
+            }
+            if settings.first_index.is_some_and(|first| startup.index < first)
+                || settings.last_index.is_some_and(|last| startup.index > last)
+            {
+                errors.push(locations.error(&path("index"), format!(
+                    "startup index {} of monitor group {} is outside of its first_index and last_index",
+                    startup.index, group.name,
+                )));
+            }
+        }
+
+        let referenced_group = match &group.fallback {
+            Some(FallbackPolicy::MonitorGroup { monitor_group }) => Some(monitor_group),
+            Some(FallbackPolicy::MergeInto { monitor_group, .. }) => Some(monitor_group),
+            _ => None,
+        };
+        if let Some(referenced_group) = referenced_group {
+            let path = at(&["fallback".into(), "monitor_group".into()]);
+            if !groups.iter().any(|other| other.name == *referenced_group) {
+                errors.push(locations.error(&path, format!(
+                    "fallback of monitor group {} refers to unknown monitor group {}",
+                    group.name, referenced_group,
+                )));
+            } else if *referenced_group == group.name {
+                errors.push(locations.error(&path, format!(
+                    "fallback of monitor group {} refers to itself",
+                    group.name,
+                )));
+            }
+        }
+    }
+}
+
+/// Find active outputs that patterns of several monitor groups match. Monitors are resolved
+/// in the order of the groups, so which group gets such an output depends on that order.
+/// Outputs that are listed exactly are taken by that entry and are skipped.
+fn find_overlapping_patterns(config: &Config, active_outputs: &[Output]) -> Vec<String> {
+    let specs = || config.groups.iter()
+        .flat_map(|group| group.monitor_specs.iter().map(move |spec| (group, spec)));
+    active_outputs.iter()
+        .sorted_by(|a, b| a.name.cmp(&b.name))
+        .filter(|output| !specs().any(|(_, spec)| spec.is_exact() && spec.matches(output)))
+        .filter_map(|output| {
+            let matching = specs()
+                .filter(|(_, spec)| !spec.is_exact() && spec.matches(output))
+                .collect_vec();
+            if matching.iter().map(|(group, _)| &group.name).unique().count() < 2 {
+                return None;
+            }
+            Some(format!(
+                "output {} matches monitors of several monitor groups: {}, the order of the groups decides which one gets it",
+                output.name,
+                matching.iter().map(|(group, spec)| format!("{} in {}", spec, group.name)).join(", "),
+            ))
+        })
+        .collect()
+}
+
+/// Print problems of the configuration file, configured monitors that are not active
+/// and active outputs that are not in any monitor group.
+/// Return `false` if the configuration has problems.
+pub fn check_config(path: &str, profile: Option<&str>, active_outputs: &[Output]) -> bool {
+    let (mut config, sources) = match Config::read(path) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            println!("error: {}", error);
+            return false;
+        },
+    };
+    let mut errors = validate(&config, &sources);
+    match config.select_profile(profile, active_outputs) {
+        Ok(()) => match &config.profile {
+            Some(name) => println!("Selected profile {}", name),
+            None if !config.profiles.is_empty() => println!("No profile matches the active outputs, using the top level groups"),
+            None => {},
+        },
+        Err(error) => errors.push(ConfigError::new(None, error)),
+    }
+    config.resolve_monitors(active_outputs);
+    let matches_output = |monitor: &String, output: &Output| {
+        output.name == *monitor || get_output_descriptor(output) == *monitor
+    };
+    let is_active = |monitor: &String| active_outputs.iter().any(|output| matches_output(monitor, output));
+    if !config.groups.iter().flat_map(|group| group.monitors.iter()).any(is_active) {
+        errors.push(ConfigError::new(None, "none of the monitor groups have an active monitor"));
+    }
+    for error in errors.iter() {
+        println!("error: {}", error);
+    }
+    for warning in find_overlapping_patterns(&config, active_outputs) {
+        println!("warning: {}", warning);
+    }
+
+    for group in config.groups.iter() {
+        let missing = group.monitors.iter()
+            .filter(|monitor| !is_active(monitor))
+            .map(|monitor| monitor.as_str())
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            println!("Inactive monitors in monitor group {}: {}", group.name, missing.join(", "));
+        }
+    }
+    let unmanaged = active_outputs.iter()
+        .filter(|output| !config.groups.iter()
+            .flat_map(|group| group.monitors.iter())
+            .any(|monitor| matches_output(monitor, output)))
+        .sorted_by(|a, b| a.name.cmp(&b.name));
+    for output in unmanaged {
+        println!("Unmanaged output: {} ({})", output.name, get_output_descriptor(output));
+    }
+
+    if errors.is_empty() {
+        println!("Config is valid");
+    }
+    errors.is_empty()
+}
+
+
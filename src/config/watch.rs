@@ -0,0 +1,32 @@
+use std::path::Path;
+use std::thread;
+
+use inotify::{Inotify, WatchMask};
+
+
+/// Call `on_change` from a background thread every time the file at `path` is written
+/// or replaced. The parent directory is watched because editors often save a file
+/// by replacing it, which would end a watch on the file itself.
+///
+/// # Panics
+/// Panics if the path can not be watched
+pub fn watch_file(path: &Path, on_change: impl Fn() + Send + 'static) {
+    let directory = path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let file_name = path.file_name().expect("Config path has no file name").to_os_string();
+
+    let mut inotify = Inotify::init().expect("Failed to initialize inotify");
+    inotify.watches()
+        .add(directory, WatchMask::CLOSE_WRITE | WatchMask::MOVED_TO)
+        .expect("Failed to watch the config directory");
+
+    thread::spawn(move || {
+        let mut buffer = [0; 4096];
+        loop {
+            let events = inotify.read_events_blocking(&mut buffer).expect("Failed to read inotify events");
+            // one call for all events read at once, saving a file can produce several
+            if events.into_iter().any(|event| event.name == Some(file_name.as_os_str())) {
+                on_change();
+            }
+        }
+    });
+}
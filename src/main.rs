@@ -6,21 +6,38 @@ mod config;
 mod cli;
 mod sway;
 mod notify;
+mod state;
 
-use config::Config;
-use cli::{Cli, Subcommands};
+use config::{Config, MonitorGroup};
+use cli::{Cli, ConfigCommand, SnapshotCommand, Subcommands};
 use notify::maybe_send_update_notification;
 
 
 
+/// Get a configured monitor group or exit with an error
+fn get_monitor_group<'a>(config: &'a Config, name: &str) -> &'a MonitorGroup {
+    config.get_group(name).unwrap_or_else(|| {
+        eprintln!("Monitor group not configured");
+        std::process::exit(1);
+    })
+}
+
 fn main() {
     let args = Cli::parse();
 
+    // The schema does not depend on sway
+    if let Subcommands::Config { command: ConfigCommand::Schema } = &args.command {
+        let schema = serde_json::to_string_pretty(&config::get_config_schema())
+            .expect("Failed to serialize the config schema");
+        println!("{}", schema);
+        return;
+    }
+
     // Set the SWAYSOCK env var for this process to the option if provided
     // Connection::new() will read this env var to get the socket path
-    args.socket.map(|socket| {
+    if let Some(socket) = args.socket {
         std::env::set_var("SWAYSOCK", socket);
-    });
+    }
     // Connect to the Sway IPC
     let mut connection = match Connection::new() {
         Ok(connection) => connection,
@@ -30,18 +47,102 @@ fn main() {
         }
     };
 
-    let config = Config::load(&args.config);
-    if config.groups.len() == 0 {
-        eprintln!("No monitor groups configured!");
+    let config_path = args.config.clone()
+        .map_or_else(config::find_config_path, Ok)
+        .map_err(|searched_paths| format!("No config file found, searched:\n  {}", searched_paths.join("\n  ")));
+
+    // Config commands have to work with an invalid or missing config
+    if let Subcommands::Config { command } = &args.command {
+        let active_outputs = sway::utils::get_active_monitors(&mut connection);
+        match command {
+            ConfigCommand::Check => {
+                let valid = config_path.as_ref()
+                    .inspect_err(|error| println!("error: {}", error))
+                    .is_ok_and(|path| config::check_config(path, args.profile.as_deref(), &active_outputs));
+                if !valid {
+                    std::process::exit(1);
+                }
+            },
+            ConfigCommand::Generate { output, interactive, force } => {
+                let generated = output.clone()
+                    .or(config_path.clone().ok())
+                    .map_or_else(config::default_config_path, Ok)
+                    .and_then(|path| config::generate_config(&active_outputs, &path, *interactive, *force));
+                if let Err(error) = generated {
+                    eprintln!("{}", error);
+                    std::process::exit(1);
+                }
+            },
+            ConfigCommand::SwayBindings { options } => {
+                let bindings = config_path
+                    .and_then(|path| Config::load_for_outputs(&path, args.profile.as_deref(), &active_outputs))
+                    .and_then(|config| {
+                        let mut command = "mumowrk".to_string();
+                        if let Some(path) = &args.config {
+                            // sway does not run the bindings in the current working directory
+                            let path = std::fs::canonicalize(path)
+                                .map_err(|error| format!("Failed to resolve config path {}: {}", path, error))?;
+                            command.push_str(&format!(" --config {}", config::shell_quote(&path.to_string_lossy())));
+                        }
+                        if let Some(profile) = &args.profile {
+                            command.push_str(&format!(" --profile {}", config::shell_quote(profile)));
+                        }
+                        config::format_sway_bindings(&config, &command, options)
+                    });
+                match bindings {
+                    Ok(bindings) => print!("{}", bindings),
+                    Err(error) => {
+                        eprintln!("{}", error);
+                        std::process::exit(1);
+                    },
+                }
+            },
+            ConfigCommand::Schema => unreachable!("Schema is printed before connecting to sway"),
+        }
         return;
     }
 
+    let config_path = match config_path {
+        Ok(path) => path,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        },
+    };
+    let active_outputs = sway::utils::get_active_monitors(&mut connection);
+    let config = match Config::load_for_outputs(&config_path, args.profile.as_deref(), &active_outputs) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        },
+    };
+    // Commands that target the primary monitor by default cannot work without it
+    let active_monitors = sway::commands::get_active_monitor_names(&mut connection);
+    let primary_group_name = config.get_primary_monitor(&active_monitors)
+        .map(|(group, _)| group.get_name().to_string());
+    let needs_primary_monitor = match &args.command {
+        Subcommands::Init { .. } => true,
+        Subcommands::Switch { mon_group, .. }
+        | Subcommands::Rotate { mon_group, .. }
+        | Subcommands::SwapMonitors { mon_group, .. } => mon_group.is_none(),
+        _ => false,
+    };
+    if needs_primary_monitor && primary_group_name.is_none() {
+        eprintln!("None of the configured monitors are active, see `mumowrk config check`");
+        std::process::exit(1);
+    }
+
+    // Workspace groups that exist before the command, to compact only the disappeared ones
+    let workspace_group_indices = config.auto_compact
+        .then(|| workspaces::get_workspace_group_indices(&mut connection));
+
     match args.command {
-        Subcommands::Init => {
-            workspaces::init_workspaces(&mut connection, &config);
+        Subcommands::Init { startup } => {
+            workspaces::init_workspaces(&mut connection, &config, startup);
         },
         Subcommands::Switch { destination, mon_group, notify } => {
-            let target_mon_group = mon_group.unwrap_or(config.groups[0].get_name().to_string());
+            let target_mon_group = mon_group.or(primary_group_name).expect("The primary monitor group is checked above");
             workspaces::switch_workspace_groups(
                 &mut connection,
                 &config,
@@ -49,30 +150,119 @@ fn main() {
                 &destination,
             );
 
+            workspaces::maybe_compact_workspace_groups(&mut connection, &config, workspace_group_indices.as_ref());
+
             // @TODO: add this to other subcommands that switch workspaces
+            let notify = notify.unwrap_or_else(|| config.get_group(&target_mon_group)
+                .map(|group| group.get_notify())
+                .unwrap_or_default());
             maybe_send_update_notification(&mut connection, notify, &config, &target_mon_group);
         },
-        Subcommands::MoveGroup { from, to, mon_group } => {
-            workspaces::swap_workspace_groups(
-                &mut connection,
-                &config,
-                from,
-                &to,
-                mon_group.as_ref(),
-            );
+        Subcommands::MoveGroup { from, to, mon_group, insert } => {
+            let move_group = if insert {
+                workspaces::insert_workspace_group
+            } else {
+                workspaces::swap_workspace_groups
+            };
+            let monitor_group = match mon_group {
+                Some(group_name) => get_monitor_group(&config, &group_name),
+                None => config.get_primary_group(),
+            };
+            move_group(&mut connection, monitor_group, from, &to);
         },
-        Subcommands::MoveContainer { destination, focus, mon_group } => {
+        Subcommands::Merge { from, to, mon_group } => {
+            let monitor_group = match mon_group {
+                Some(group_name) => get_monitor_group(&config, &group_name),
+                None => config.get_primary_group(),
+            };
+            workspaces::merge_workspace_groups(&mut connection, &config, monitor_group, from, &to);
+            workspaces::maybe_compact_workspace_groups(&mut connection, &config, workspace_group_indices.as_ref());
+        },
+        Subcommands::MoveContainer { destination, focus, mon_group, criteria } => {
             workspaces::move_container_to_workspace_group(
                 &mut connection,
                 &config,
                 &destination,
                 mon_group.as_ref(),
                 focus,
+                &criteria,
+            );
+            workspaces::maybe_compact_workspace_groups(&mut connection, &config, workspace_group_indices.as_ref());
+        },
+        Subcommands::Rotate { index, reverse, mon_group } => {
+            let monitor_group = get_monitor_group(
+                &config,
+                &mon_group.or(primary_group_name).expect("The primary monitor group is checked above"),
+            );
+            workspaces::rotate_workspace_group(&mut connection, monitor_group, index.as_ref(), reverse);
+        },
+        Subcommands::SwapMonitors { monitor_a, monitor_b, index, mon_group } => {
+            let monitor_group = get_monitor_group(
+                &config,
+                &mon_group.or(primary_group_name).expect("The primary monitor group is checked above"),
             );
+            workspaces::swap_monitors(&mut connection, monitor_group, index.as_ref(), monitor_a, monitor_b);
         },
-        Subcommands::Reorganize {  } => {
-            workspaces::reorganize_everything(&mut connection, &config);
+        Subcommands::Transfer { index, from, to, as_index } => {
+            let (Some(from_group), Some(to_group)) = (config.get_group(&from), config.get_group(&to)) else {
+                eprintln!("Monitor group not configured");
+                return;
+            };
+            workspaces::transfer_workspace_group(
+                &mut connection,
+                index,
+                from_group,
+                to_group,
+                as_index.unwrap_or(index),
+                config.merge_layout,
+            );
+        },
+        Subcommands::Compact { mon_group } => {
+            let monitor_group = match mon_group {
+                Some(group_name) => get_monitor_group(&config, &group_name),
+                None => config.get_primary_group(),
+            };
+            workspaces::compact_workspace_groups(&mut connection, monitor_group);
+        },
+        Subcommands::Gc { mon_group } => {
+            let monitor_group = match mon_group {
+                Some(group_name) => match config.get_group(&group_name) {
+                    Some(monitor_group) => Some(monitor_group),
+                    None => {
+                        eprintln!("Monitor group not configured");
+                        return;
+                    },
+                },
+                None => None,
+            };
+            workspaces::remove_empty_workspaces(&mut connection, &config, monitor_group);
+        },
+        Subcommands::Reorganize { dry_run, json, verbose, mon_group, index, strategy } => {
+            if mon_group.as_ref().is_some_and(|group_name| config.get_group(group_name).is_none()) {
+                eprintln!("Monitor group not configured");
+                return;
+            }
+            let scope = workspaces::ReorganizeScope { mon_group, index, strategy };
+            if dry_run {
+                workspaces::print_reorganization_plan(&mut connection, &config, &scope, json);
+            } else {
+                workspaces::reorganize_everything(&mut connection, &config, &scope, verbose);
+            }
         }
+        Subcommands::Snapshot { command } => match command {
+            SnapshotCommand::Save => {
+                match workspaces::save_snapshot(&mut connection, &config) {
+                    Ok(snapshot) => println!("Saved snapshot {}", snapshot.id),
+                    Err(error) => {
+                        eprintln!("{}", error);
+                        std::process::exit(1);
+                    },
+                }
+            },
+            SnapshotCommand::List => workspaces::print_snapshot_list(),
+            SnapshotCommand::Show { id } => workspaces::print_snapshot(id),
+            SnapshotCommand::Restore { id } => workspaces::restore_snapshot(&mut connection, id),
+        },
         Subcommands::Print { waybar_module, subscribe } => {
             let printer = if waybar_module {
                 workspaces::print_waybar_module
@@ -80,10 +270,11 @@ fn main() {
                 workspaces::print_state_plain
             };
             if subscribe {
-                workspaces::subscribe_and_print(&mut connection, printer);
+                workspaces::subscribe_and_print(&mut connection, config, &config_path, args.profile.as_deref(), printer);
             } else {
-                printer(&mut connection);
+                printer(&mut connection, &config);
             }
         },
+        Subcommands::Config { .. } => unreachable!("config commands are handled before loading the config"),
     };
 }
@@ -4,8 +4,7 @@ use dbus::blocking::{BlockingSender, Connection as DbusConnection};
 use std::time::Duration;
 use swayipc::Connection as SwayConnection;
 
-use crate::cli::NotificationVerbosity;
-use crate::config::Config;
+use crate::config::{Config, NotificationVerbosity};
 use crate::sway::commands::get_active_monitor_names;
 use crate::sway::utils::get_output_name_by_descriptor;
 use crate::workspaces;
@@ -20,21 +19,36 @@ pub fn maybe_send_update_notification(
         NotificationVerbosity::None =>
             { return; },
         NotificationVerbosity::Index =>
-            format!("<u><b>{}</b></u>", workspaces::get_current_index(connection, &target_mon_group)),
+            format!("<u><b>{}</b></u>", workspaces::get_current_index(connection, target_mon_group)),
         NotificationVerbosity::Summary =>
-            workspaces::get_state_rich_text(connection),
+            workspaces::get_state_rich_text(connection, config),
     };
     let active_monitors = get_active_monitor_names(connection);
-    let monitor_group = config.get_group(&target_mon_group).unwrap();
-    let target_monitor = monitor_group.monitors[
-        monitor_group.get_main_monitor_index(&active_monitors)
-    ].clone();
+    let monitor_group = config.get_group(target_mon_group).unwrap();
+    let target_monitor = match monitor_group.get_main_monitor_index(&active_monitors) {
+        Some(main_monitor_index) => monitor_group.monitors[main_monitor_index].clone(),
+        // Notify on the monitor where the workspaces are parked
+        None => match config.get_fallback_monitor(monitor_group, &active_monitors) {
+            Some(monitor) => monitor,
+            None => { return; },
+        },
+    };
     // We need to translate to output name if monitor is configured using its descriptor
     let output_name = get_output_name_by_descriptor(connection, &target_monitor).unwrap_or(target_monitor);
 
     dbus_notify(&notification_text, &output_name);
 }
 
+/// Send a notification about a problem to the focused output
+pub fn send_error_notification(connection: &mut SwayConnection, text: &str) {
+    let focused_output = connection.get_outputs().expect("Failed to get outputs")
+        .into_iter()
+        .find(|output| output.focused)
+        .map(|output| output.name)
+        .unwrap_or_default();
+    dbus_notify(text, &focused_output);
+}
+
 pub(crate) fn dbus_notify(text: &str, target_monitor: &str) {
     let connection = DbusConnection::new_session().expect("Failed to connect to D-Bus");
 
@@ -52,7 +66,7 @@ pub(crate) fn dbus_notify(text: &str, target_monitor: &str) {
         // icon
         .append1("")
         // summary
-        .append1(&format!("mon:{}", target_monitor))
+        .append1(format!("mon:{}", target_monitor))
         // body
         .append1(text)
         // actions (none)
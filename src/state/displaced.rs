@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Serialize, Deserialize};
+
+use super::state_dir;
+
+
+const DISPLACED_FILE_NAME: &str = "displaced.json";
+
+/// Containers and workspaces that were moved away from their original monitor
+/// index during a reorganization, so that they can be moved back once the monitor
+/// is active again
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct DisplacedState {
+    /// Original workspace name by container ID for containers merged into another workspace
+    #[serde(default)]
+    pub containers: HashMap<i64, String>,
+    /// Original workspace name by current workspace name for renamed workspaces
+    #[serde(default)]
+    pub workspaces: HashMap<String, String>,
+}
+
+impl DisplacedState {
+    fn path() -> PathBuf {
+        state_dir().join(DISPLACED_FILE_NAME)
+    }
+
+    /// Load the recorded state, a missing or broken file results in an empty state
+    pub fn load() -> Self {
+        let Ok(state_str) = fs::read_to_string(Self::path()) else {
+            return Self::default();
+        };
+        serde_json::from_str(&state_str)
+            .inspect_err(|e| eprintln!("Could not parse displaced containers state: {}", e))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let _ = fs::create_dir_all(state_dir())
+            .and_then(|_| fs::write(Self::path(), serde_json::to_string(self).unwrap()))
+            .inspect_err(|e| eprintln!("Could not save displaced containers state: {}", e));
+    }
+
+    /// Return the original name of a workspace, following its recorded renames
+    pub fn original_workspace<'a>(&'a self, name: &'a str) -> &'a str {
+        self.workspaces.get(name).map(String::as_str).unwrap_or(name)
+    }
+}
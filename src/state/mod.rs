@@ -0,0 +1,18 @@
+mod displaced;
+mod snapshot;
+
+use std::path::PathBuf;
+
+pub use displaced::DisplacedState;
+pub use snapshot::Snapshot;
+
+
+/// Directory for state persisted between invocations,
+/// `$XDG_STATE_HOME/mumowrk` or `~/.local/state/mumowrk` if the variable is not set
+pub fn state_dir() -> PathBuf {
+    let state_home = std::env::var("XDG_STATE_HOME")
+        .ok()
+        .filter(|path| !path.is_empty())
+        .unwrap_or_else(|| shellexpand::tilde("~/.local/state").into_owned());
+    PathBuf::from(state_home).join("mumowrk")
+}
@@ -0,0 +1,151 @@
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Serialize, Deserialize};
+use swayipc::Node;
+
+use crate::sway::utils::{find_containers, get_workspace_nodes};
+use super::state_dir;
+
+
+const SNAPSHOTS_DIR_NAME: &str = "snapshots";
+
+/// A window recorded in a snapshot together with its workspace
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SnapshotWindow {
+    pub con_id: i64,
+    pub app_id: Option<String>,
+    pub title: Option<String>,
+    pub workspace: String,
+}
+
+/// Recorded state of the node tree and of the windows in workspaces
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Snapshot {
+    /// Creation time in milliseconds since the Unix epoch, also used as the ID
+    pub id: u128,
+    pub windows: Vec<SnapshotWindow>,
+    pub tree: Node,
+}
+
+impl Snapshot {
+    /// Record the windows of all workspaces in the tree
+    pub fn capture(tree: Node) -> Self {
+        let windows = get_workspace_nodes(&tree)
+            .flat_map(|workspace_node| {
+                let workspace = workspace_node.name.clone().unwrap_or_default();
+                // views are the containers that belong to an application
+                find_containers(workspace_node, &|node| node.pid.is_some())
+                    .into_iter()
+                    .map(move |node| SnapshotWindow {
+                        con_id: node.id,
+                        app_id: node.app_id.clone().or_else(|| {
+                            node.window_properties.as_ref().and_then(|props| props.class.clone())
+                        }),
+                        title: node.name.clone(),
+                        workspace: workspace.clone(),
+                    })
+            })
+            .collect();
+        let id = SystemTime::now().duration_since(UNIX_EPOCH)
+            .expect("System time is before the Unix epoch")
+            .as_millis();
+        Self { id, windows, tree }
+    }
+
+    fn dir() -> PathBuf {
+        state_dir().join(SNAPSHOTS_DIR_NAME)
+    }
+
+    fn path(id: u128) -> PathBuf {
+        Self::dir().join(format!("{}.json", id))
+    }
+
+    /// Save the snapshot and remove the oldest snapshots exceeding `retention`.
+    /// A snapshot with the same ID (taken in the same millisecond) is not overwritten.
+    pub fn save(&self, retention: usize) -> Result<(), String> {
+        fs::create_dir_all(Self::dir())
+            .and_then(|_| OpenOptions::new().write(true).create_new(true).open(Self::path(self.id)))
+            .and_then(|mut file| file.write_all(serde_json::to_string(self).unwrap().as_bytes()))
+            .map_err(|e| match e.kind() {
+                ErrorKind::AlreadyExists => format!("Could not save snapshot: snapshot {} already exists", self.id),
+                _ => format!("Could not save snapshot: {}", e),
+            })?;
+
+        let ids = Self::list_ids();
+        for id in ids.iter().take(ids.len().saturating_sub(retention)) {
+            let _ = fs::remove_file(Self::path(*id))
+                .inspect_err(|e| eprintln!("Could not remove old snapshot {}: {}", id, e));
+        }
+        Ok(())
+    }
+
+    /// IDs of saved snapshots from the oldest to the newest
+    pub fn list_ids() -> Vec<u128> {
+        let Ok(entries) = fs::read_dir(Self::dir()) else {
+            return Vec::new();
+        };
+        let mut ids: Vec<u128> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.path().file_stem()?.to_str()?.parse().ok())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// Load a saved snapshot by its ID
+    pub fn load(id: u128) -> Result<Self, String> {
+        let snapshot_str = fs::read_to_string(Self::path(id))
+            .map_err(|e| format!("Could not read snapshot {}: {}", id, e))?;
+        serde_json::from_str(&snapshot_str)
+            .map_err(|e| format!("Could not parse snapshot {}: {}", id, e))
+    }
+
+    /// Creation time formatted as `YYYY-MM-DD HH:MM:SS` in UTC
+    pub fn format_time(&self) -> String {
+        format_time(self.id)
+    }
+}
+
+/// Time in milliseconds since the Unix epoch formatted as `YYYY-MM-DD HH:MM:SS` in UTC
+fn format_time(milliseconds: u128) -> String {
+    let seconds = (milliseconds / 1000) as i64;
+    let (days, time) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+    // Convert days since the epoch to a civil date, see
+    // https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year, month, day, time / 3600, time % 3600 / 60, time % 60,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_time;
+
+    #[test]
+    fn formats_the_epoch() {
+        assert_eq!(format_time(0), "1970-01-01 00:00:00");
+    }
+
+    #[test]
+    fn formats_a_leap_day() {
+        assert_eq!(format_time(1_709_210_096_789), "2024-02-29 12:34:56");
+    }
+
+    #[test]
+    fn formats_the_end_of_a_year() {
+        assert_eq!(format_time(1_735_689_599_999), "2024-12-31 23:59:59");
+    }
+}
@@ -47,7 +47,7 @@ pub fn get_active_monitor_names(connection: &mut Connection) -> HashSet<String>
         .filter(|output| output.active)
         .flat_map(|output| [
             output.name.clone(),
-            get_output_descriptor(&output),
+            get_output_descriptor(output),
         ].into_iter())
         .collect()
 }
@@ -63,7 +63,7 @@ pub fn get_active_outputs(connection: &mut Connection) -> HashMap<String, String
         .filter(|output| output.active)
         .map(|output| (
             output.name.clone(),
-            get_output_descriptor(&output),
+            get_output_descriptor(output),
         ))
         .collect()
 }
@@ -89,6 +89,11 @@ pub fn rename_workspace<IdA: std::fmt::Display, IdB: std::fmt::Display>(connecti
     ).expect("Failed to rename workspace");
 }
 
+/// Format a `rename workspace OLD to NEW` command
+pub fn get_rename_workspace_command<IdA: std::fmt::Display, IdB: std::fmt::Display>(old: &IdA, new: &IdB) -> String {
+    format!("rename workspace \"{}\" to \"{}\"", old, new)
+}
+
 /// Run a `move container to workspace` command over the sway IPC connection
 /// 
 /// # Panics
@@ -109,6 +114,69 @@ pub fn move_container_by_id<Id: std::fmt::Display>(connection: &mut Connection,
     ).expect("Failed to move container");
 }
 
+/// Run `move position` and `resize set` commands for a floating container over the sway IPC connection.
+/// Position and size are in percent of its workspace.
+/// 
+/// # Panics
+/// Panics if the command fails
+pub fn place_floating_container_by_id(connection: &mut Connection, container_id: i64, x: u8, y: u8, width: u8, height: u8) {
+    connection.run_command(format!(
+        "[con_id=\"{id}\"] resize set width {} ppt height {} ppt; [con_id=\"{id}\"] move position {} ppt {} ppt",
+        width, height, x, y, id = container_id,
+    )).expect("Failed to place floating container");
+}
+
+/// Move containers to a workspace and wrap them in a single container with the given layout
+/// (e.g. `tabbed` or `stacking`) over the sway IPC connection. The order of containers is preserved.
+/// 
+/// # Panics
+/// Panics if the command fails
+pub fn move_containers_wrapped_by_id<Id: std::fmt::Display>(connection: &mut Connection, container_ids: &[i64], workspace_id: &Id, layout: &str) {
+    let Some((first_id, other_ids)) = container_ids.split_first() else {
+        return;
+    };
+    // Each container is moved next to the previous one which holds the (unique) mark
+    let mark = format!("_mumowrk_wrap_{}", first_id);
+    let commands = [
+        format!("[con_id=\"{}\"] move container to workspace {}", first_id, workspace_id),
+        format!("[con_id=\"{}\"] split vertical", first_id),
+        format!("[con_id=\"{}\"] layout {}", first_id, layout),
+        format!("[con_id=\"{}\"] mark --add {}", first_id, mark),
+    ].into_iter()
+        .chain(other_ids.iter().flat_map(|id| [
+            format!("[con_id=\"{}\"] move container to mark {}", id, mark),
+            format!("[con_id=\"{}\"] mark --add {}", id, mark),
+        ]))
+        .chain([format!("unmark {}", mark)])
+        .collect::<Vec<_>>()
+        .join(";");
+    connection.run_command(commands).expect("Failed to move and wrap containers");
+}
+
+/// Run a `focus` command for a criteria over the sway IPC connection.
+/// Return `false` if the container does not exist.
+/// 
+/// # Panics
+/// Panics if the request fails
+pub fn focus_container_by_id(connection: &mut Connection, container_id: i64) -> bool {
+    connection.run_command(
+        format!("[con_id=\"{}\"] focus", container_id)
+    ).expect("Failed to focus container")
+        .iter()
+        .all(|outcome| outcome.is_ok())
+}
+
+/// Run an `exec` command over the sway IPC connection to start a command
+/// on the focused workspace
+/// 
+/// # Panics
+/// Panics if the command fails
+pub fn exec_command(connection: &mut Connection, command: &str) {
+    connection.run_command(
+        format!("exec {}", command)
+    ).expect("Failed to execute command");
+}
+
 /// Run a `workspace` command over the sway IPC connection
 /// to assign a workspace to an output
 /// 
@@ -131,6 +199,30 @@ pub fn move_workspace_to_monitor<Id: std::fmt::Display>(connection: &mut Connect
     ).expect("Failed to move workspace to a monitor");
 }
 
+/// Remove an empty workspace that is not visible via the sway IPC connection.
+/// Sway destroys an empty workspace once it is not visible anymore, so the workspace
+/// is shown and then replaced by `visible_workspace_id` (visible on the same output).
+/// 
+/// # Panics
+/// Panics if the command fails
+pub fn remove_empty_workspace<IdA: std::fmt::Display, IdB: std::fmt::Display>(
+    connection: &mut Connection,
+    workspace_id: &IdA,
+    visible_workspace_id: &IdB,
+) {
+    connection.run_command(
+        format!("workspace \"{}\"; workspace \"{}\"", workspace_id, visible_workspace_id)
+    ).expect("Failed to remove an empty workspace");
+}
+
+/// Format a pair of commands to focus a workspace and move it to a monitor
+pub fn get_move_workspace_to_monitor_command<Id: std::fmt::Display>(
+    workspace_id: &Id,
+    monitor: &str,
+) -> String {
+    format!("workspace \"{}\"; move workspace to output \"{}\"", workspace_id, monitor)
+}
+
 /// Format a pair of commands to assign and actiate a workspace
 /// on a specific monitor
 pub fn get_assign_and_focus_workspace_command<Id: std::fmt::Display>(
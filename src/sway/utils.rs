@@ -1,4 +1,6 @@
-use swayipc::{Connection, Output};
+use std::collections::HashMap;
+
+use swayipc::{Connection, Node, NodeType, Output};
 
 // TODO move some of the helpers tha don't edit state from ./commands.rs to here and rename all these files
 
@@ -15,6 +17,16 @@ pub fn get_active_monitors(connection: &mut Connection) -> Vec<Output> {
         .collect()
 }
 
+/// Get positions (x, y) of outputs by both their name and descriptor
+pub fn get_output_positions(outputs: &[Output]) -> HashMap<String, (i32, i32)> {
+    outputs.iter()
+        .flat_map(|output| [
+            (output.name.clone(), (output.rect.x, output.rect.y)),
+            (get_output_descriptor(output), (output.rect.x, output.rect.y)),
+        ])
+        .collect()
+}
+
 /// Concatenate output make, model, and serial number into a descriptor.
 /// This format is recognized by `sway-output`.
 pub fn get_output_descriptor(output: &Output) -> String {
@@ -32,7 +44,7 @@ pub fn get_output_descriptor(output: &Output) -> String {
 /// Panics if requesting list of ouputs from Sway IPC fails
 pub fn get_output_descriptor_by_name(connection: &mut Connection, name: &str) -> Option<String> {
     get_active_monitors(connection).iter()
-        .find(|output| &output.name == name)
+        .find(|output| output.name == name)
         .map(get_output_descriptor)
 }
 
@@ -45,3 +57,31 @@ pub fn get_output_name_by_descriptor(connection: &mut Connection, descriptor: &s
         .find(|output| get_output_descriptor(output) == descriptor)
         .map(|output| output.name.clone())
 }
+
+/// Iterate over all workspace nodes in the node tree, skipping the scratchpad
+pub fn get_workspace_nodes(tree: &Node) -> impl Iterator<Item = &Node> {
+    tree.nodes.iter()
+        .flat_map(|output_node| output_node.nodes.iter())
+        .filter(|node| node.node_type == NodeType::Workspace && node.name.as_deref() != Some("__i3_scratch"))
+}
+
+/// Whether a workspace node has no containers (tiled nor floating)
+pub fn is_empty_workspace_node(node: &Node) -> bool {
+    node.nodes.is_empty() && node.floating_nodes.is_empty()
+}
+
+/// Collect containers (tiled and floating) under `node` that satisfy the predicate.
+/// Children of a matching container are not searched because they would be moved
+/// together with it anyway.
+pub fn find_containers<'tree>(node: &'tree Node, predicate: &impl Fn(&Node) -> bool) -> Vec<&'tree Node> {
+    node.nodes.iter()
+        .chain(node.floating_nodes.iter())
+        .flat_map(|child| {
+            if predicate(child) {
+                vec![child]
+            } else {
+                find_containers(child, predicate)
+            }
+        })
+        .collect()
+}
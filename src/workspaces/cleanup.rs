@@ -0,0 +1,52 @@
+use swayipc::{Connection, Workspace};
+
+use crate::config::{Config, MonitorGroup};
+use crate::sway::utils::{get_workspace_nodes, is_empty_workspace_node};
+use super::focus::FocusState;
+use super::organize::ReorganizeState;
+use super::plan::{execute_plan, Action, PlannedAction};
+use super::workspace_id::WorkspaceId;
+
+
+/// Remove empty managed workspaces that are not visible.
+/// Only workspaces of `monitor_group` are removed if given, otherwise of all configured monitor groups.
+pub fn remove_empty_workspaces(connection: &mut Connection, config: &Config, monitor_group: Option<&MonitorGroup>) {
+    let state = ReorganizeState::query(connection);
+    let focus = FocusState::capture(&state);
+
+    let plan: Vec<PlannedAction> = state.workspaces.iter()
+        .filter(|workspace| WorkspaceId::parse_safe(&workspace.name).is_some_and(|id| match monitor_group {
+            Some(monitor_group) => id.get_monitor_group_name() == monitor_group.name,
+            None => config.get_group(id.get_monitor_group_name()).is_some(),
+        }))
+        .filter_map(|workspace| plan_remove_empty_workspace(&state, workspace, "garbage collection"))
+        .collect();
+    if plan.is_empty() {
+        println!("No empty workspaces to remove");
+        return;
+    }
+    execute_plan(connection, &plan, false);
+    focus.restore(connection, &plan);
+}
+
+/// Plan removing the workspace if it is empty and not visible.
+/// Return `None` if the workspace is not empty, is visible or there is no other
+/// workspace visible on its output to switch back to.
+pub(super) fn plan_remove_empty_workspace(
+    state: &ReorganizeState,
+    workspace: &Workspace,
+    reason: &str,
+) -> Option<PlannedAction> {
+    if workspace.visible {
+        return None;
+    }
+    get_workspace_nodes(&state.tree)
+        .find(|node| node.name.as_ref() == Some(&workspace.name))
+        .filter(|node| is_empty_workspace_node(node))?;
+    let visible = state.workspaces.iter()
+        .find(|other| other.visible && other.output == workspace.output)?;
+    Some(PlannedAction::new(
+        Action::RemoveWorkspace { workspace: workspace.name.clone(), visible: visible.name.clone() },
+        reason,
+    ))
+}
@@ -0,0 +1,91 @@
+use std::collections::{HashMap, HashSet};
+
+use itertools::Itertools;
+use swayipc::{Connection, Workspace};
+
+use crate::config::{Config, MonitorGroup, FIRST_WORKSPACE_GROUP};
+use crate::sway::commands::get_workspaces;
+use super::utils::rename_workspaces;
+use super::workspace_id::WorkspaceId;
+
+
+/// Renumber existing workspace groups of the monitor group to a contiguous range
+/// starting at `FIRST_WORKSPACE_GROUP` while preserving their order.
+pub fn compact_workspace_groups(connection: &mut Connection, monitor_group: &MonitorGroup) {
+    let workspace_ids: Vec<WorkspaceId> = get_workspaces(connection).iter()
+        .filter_map(|workspace| WorkspaceId::parse_safe(&workspace.name))
+        .filter(|id| id.get_monitor_group_name() == monitor_group.name)
+        .collect();
+
+    let new_indices = get_compacted_indices(workspace_ids.iter().map(|id| id.get_index()));
+    let renames: Vec<(WorkspaceId, WorkspaceId)> = workspace_ids.into_iter()
+        .filter_map(|id| {
+            let new_index = new_indices[&id.get_index()];
+            (new_index != id.get_index()).then(|| {
+                let new_id = WorkspaceId::new(&monitor_group.name, id.get_monitor_index(), new_index);
+                (id, new_id)
+            })
+        })
+        .collect();
+
+    // Renaming keeps the containers and visibility of the workspaces intact
+    rename_workspaces(connection, &renames);
+}
+
+/// New index of every workspace group index after compaction
+fn get_compacted_indices(indices: impl Iterator<Item = i32>) -> HashMap<i32, i32> {
+    indices.unique()
+        .sorted()
+        .zip(FIRST_WORKSPACE_GROUP..)
+        .collect()
+}
+
+/// Get indices of the existing workspace groups of every monitor group
+pub fn get_workspace_group_indices(connection: &mut Connection) -> HashMap<String, HashSet<i32>> {
+    group_indices(&get_workspaces(connection))
+}
+
+fn group_indices(workspaces: &[Workspace]) -> HashMap<String, HashSet<i32>> {
+    workspaces.iter()
+        .filter_map(|workspace| WorkspaceId::parse_safe(&workspace.name))
+        .map(|id| (id.get_monitor_group_name().to_string(), id.get_index()))
+        .into_group_map()
+        .into_iter()
+        .map(|(group, indices)| (group, indices.into_iter().collect()))
+        .collect()
+}
+
+/// Compact the monitor groups in which a workspace group disappeared since
+/// `previous_indices` were taken, if enabled in the config. A monitor group is left
+/// as it is if compacting it would renumber its visible workspace group
+/// (e.g. the one that was just switched to).
+/// `previous_indices` are only taken if auto compaction is enabled.
+pub fn maybe_compact_workspace_groups(
+    connection: &mut Connection,
+    config: &Config,
+    previous_indices: Option<&HashMap<String, HashSet<i32>>>,
+) {
+    let Some(previous_indices) = previous_indices.filter(|_| config.auto_compact) else {
+        return;
+    };
+    let workspaces = get_workspaces(connection);
+    let current_indices = group_indices(&workspaces);
+    for monitor_group in &config.groups {
+        let no_indices = HashSet::new();
+        let indices = current_indices.get(&monitor_group.name).unwrap_or(&no_indices);
+        let disappeared = previous_indices.get(&monitor_group.name)
+            .is_some_and(|previous| previous.iter().any(|index| !indices.contains(index)));
+        if !disappeared {
+            continue;
+        }
+        let new_indices = get_compacted_indices(indices.iter().copied());
+        let renumbers_visible = workspaces.iter()
+            .filter(|workspace| workspace.visible)
+            .filter_map(|workspace| WorkspaceId::parse_safe(&workspace.name))
+            .filter(|id| id.get_monitor_group_name() == monitor_group.name)
+            .any(|id| new_indices[&id.get_index()] != id.get_index());
+        if !renumbers_visible {
+            compact_workspace_groups(connection, monitor_group);
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use itertools::Itertools;
+use swayipc::{Connection, Node, NodeType};
+
+use crate::sway::commands::{focus_container_by_id, focus_workspace, get_focus_workspace_command, get_workspaces};
+use super::organize::ReorganizeState;
+use super::plan::{Action, PlannedAction};
+use super::workspace_id::WorkspaceId;
+
+
+/// Focus and visible workspace groups captured before reorganizing
+pub(super) struct FocusState {
+    focused_container: Option<i64>,
+    focused_workspace: Option<String>,
+    /// Visible workspace group index by monitor group name
+    visible_indices: Vec<(String, i32)>,
+}
+
+impl FocusState {
+    pub fn capture(state: &ReorganizeState) -> Self {
+        let focused_container = find_focused_node(&state.tree)
+            .filter(|node| matches!(node.node_type, NodeType::Con | NodeType::FloatingCon))
+            .map(|node| node.id);
+        let focused_workspace = state.workspaces.iter()
+            .find(|workspace| workspace.focused)
+            .map(|workspace| workspace.name.clone());
+        let visible_indices = state.workspaces.iter()
+            .filter(|workspace| workspace.visible)
+            .filter_map(|workspace| WorkspaceId::parse_safe(&workspace.name))
+            .unique_by(|id| id.get_monitor_group_name().to_string())
+            .map(|id| (id.get_monitor_group_name().to_string(), id.get_index()))
+            .collect();
+        Self { focused_container, focused_workspace, visible_indices }
+    }
+
+    /// Show the previously visible workspace groups and focus the previously focused
+    /// container, or its workspace (following renames in `plan`) if it was empty.
+    pub fn restore(&self, connection: &mut Connection, plan: &[PlannedAction]) {
+        let workspaces = get_workspaces(connection);
+        let commands = workspaces.iter()
+            .filter(|workspace| WorkspaceId::parse_safe(&workspace.name).is_some_and(|id| {
+                self.visible_indices.contains(&(id.get_monitor_group_name().to_string(), id.get_index()))
+            }))
+            .map(|workspace| get_focus_workspace_command(&workspace.name))
+            .join(";");
+        if !commands.is_empty() {
+            connection.run_command(commands).expect("Failed to show workspace groups");
+        }
+
+        if self.focused_container.is_some_and(|con_id| focus_container_by_id(connection, con_id)) {
+            return;
+        }
+        if let Some(focused_workspace) = &self.focused_workspace {
+            let renamed_workspace = plan.iter()
+                .fold(focused_workspace.clone(), |name, planned_action| match &planned_action.action {
+                    Action::RenameWorkspace { from, to } if *from == name => to.clone(),
+                    _ => name,
+                });
+            if workspaces.iter().any(|workspace| workspace.name == renamed_workspace) {
+                focus_workspace(connection, &renamed_workspace);
+            }
+        }
+    }
+}
+
+fn find_focused_node(node: &Node) -> Option<&Node> {
+    if node.focused {
+        return Some(node);
+    }
+    node.nodes.iter()
+        .chain(node.floating_nodes.iter())
+        .find_map(find_focused_node)
+}
@@ -1,18 +1,48 @@
+use std::collections::HashSet;
+
 use swayipc::Connection;
 
 use crate::{config::{Config, FIRST_WORKSPACE_GROUP}, sway::commands::{assign_workspace_to_monitor, focus_workspace, get_active_monitor_names}};
+use super::startup::run_startup_commands;
 use super::workspace_id::WorkspaceId;
 
 
-pub fn init_workspaces(connection: &mut Connection, config: &Config) {
-    if config.groups.len() == 0 {
+/// Show the first workspace group on every active monitor and focus the primary monitor.
+/// The startup commands are executed first if `startup` is set.
+pub fn init_workspaces(connection: &mut Connection, config: &Config, startup: bool) {
+    if config.groups.is_empty() {
         return;
     }
 
     let active_monitors = get_active_monitor_names(connection);
 
-    // Assign every managed monitor a workspace per the configured grouping
+    assign_first_workspace_groups(connection, config, &active_monitors);
+    if startup && config.groups.iter().any(|group| !group.startup.is_empty()) {
+        run_startup_commands(connection, config, &active_monitors);
+        // Show the first workspace groups again after the startup commands focused their workspaces
+        assign_first_workspace_groups(connection, config, &active_monitors);
+    }
+
+    // Focus the main monitor's workspace of the first group with an active monitor
+    let Some((main_group, main_monitor_index)) = config.get_primary_monitor(&active_monitors) else {
+        return;
+    };
+    let main_workspace_id: WorkspaceId = WorkspaceId::new(&main_group.name, main_monitor_index, FIRST_WORKSPACE_GROUP);
+    focus_workspace(connection, &main_workspace_id);
+}
+
+/// Assign every managed monitor a workspace per the configured grouping
+/// and show it on the monitor
+fn assign_first_workspace_groups(connection: &mut Connection, config: &Config, active_monitors: &HashSet<String>) {
     for group in config.groups.iter() {
+        if !group.has_active_monitor(active_monitors) {
+            // Make sure the workspace gets created on the fallback monitor once it is used
+            if let Some(monitor) = config.get_fallback_monitor(group, active_monitors) {
+                let workspace_id = WorkspaceId::parked(&group.name, FIRST_WORKSPACE_GROUP);
+                assign_workspace_to_monitor(connection, &workspace_id, &monitor);
+            }
+            continue;
+        }
         for (index, monitor) in group.monitors.iter().enumerate() {
             if !active_monitors.contains(monitor) {
                 continue;
@@ -23,12 +53,4 @@ pub fn init_workspaces(connection: &mut Connection, config: &Config) {
             focus_workspace(connection, &workspace_id);
         }
     }
-
-    // Focus the main monitor's workspace
-    let main_workspace_id: WorkspaceId = WorkspaceId::new(
-        &config.groups[0].name,
-        config.get_primary_group().get_main_monitor_index(&active_monitors),
-        FIRST_WORKSPACE_GROUP,
-    );
-    focus_workspace(connection, &main_workspace_id);
 }
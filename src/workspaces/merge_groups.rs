@@ -0,0 +1,55 @@
+use std::collections::HashSet;
+
+use swayipc::Connection;
+
+use crate::config::{Config, MonitorGroup};
+use crate::sway::commands::{get_workspaces, rename_workspace};
+use super::utils::{get_target_index, move_all_containers};
+use super::workspace_id::WorkspaceId;
+
+
+/// Move all containers of workspace group `from_index` into the workspace group
+/// given by `to` of the monitor group. Containers stay on the monitor with the same index.
+pub fn merge_workspace_groups(
+    connection: &mut Connection,
+    config: &Config,
+    monitor_group: &MonitorGroup,
+    from_index: i32,
+    to: &str,
+) {
+    let workspaces = get_workspaces(connection);
+    let workspace_names: HashSet<String> = workspaces.iter()
+        .map(|workspace| workspace.name.clone())
+        .collect();
+
+    let to_index = get_target_index(&workspaces, &monitor_group.name, to);
+    if to_index == from_index {
+        println!("Source and destination workspace groups are the same. Nothing was merged.");
+        return;
+    }
+
+    // Use the existing workspaces so that even workspaces parked on
+    // a monitor outside of the group get merged
+    let from_ids: Vec<WorkspaceId> = workspaces.iter()
+        .filter_map(|workspace| WorkspaceId::parse_safe(&workspace.name))
+        .filter(|id| id.get_monitor_group_name() == monitor_group.name && id.get_index() == from_index)
+        .collect();
+    if from_ids.is_empty() {
+        println!("Workspace group {} does not exist. Nothing was merged.", from_index);
+        return;
+    }
+
+    for from_id in from_ids {
+        let to_id = WorkspaceId::new(
+            &monitor_group.name,
+            from_id.get_monitor_index(),
+            to_index,
+        );
+        if workspace_names.contains(&to_id.to_string()) {
+            move_all_containers(connection, &from_id, &to_id, config.merge_layout);
+        } else {
+            // Nothing to merge with, the workspace can simply take the new name
+            rename_workspace(connection, &from_id, &to_id);
+        }
+    }
+}
@@ -1,10 +1,21 @@
 
+mod cleanup;
+mod compact;
+mod focus;
 mod initialize;
+mod merge_groups;
 mod move_container;
 mod organize;
+mod plan;
 mod print;
+mod restore;
+mod rotate;
+mod simulate;
+mod snapshot;
+mod startup;
 mod swap_groups;
 mod switch;
+mod transfer;
 mod utils;
 mod workspace_id;
 
@@ -12,6 +23,12 @@ pub use initialize::init_workspaces;
 pub use switch::switch_workspace_groups;
 pub use print::{subscribe_and_print, print_state_plain, get_state_rich_text, get_current_index, print_waybar_module};
 pub use move_container::move_container_to_workspace_group;
-pub use swap_groups::swap_workspace_groups;
-pub use organize::reorganize_everything;
+pub use swap_groups::{insert_workspace_group, swap_workspace_groups};
+pub use merge_groups::merge_workspace_groups;
+pub use snapshot::{print_snapshot, print_snapshot_list, restore_snapshot, save_snapshot};
+pub use rotate::{rotate_workspace_group, swap_monitors};
+pub use transfer::transfer_workspace_group;
+pub use cleanup::remove_empty_workspaces;
+pub use compact::{compact_workspace_groups, get_workspace_group_indices, maybe_compact_workspace_groups};
+pub use organize::{print_reorganization_plan, reorganize_everything, ReorganizeScope};
 pub use workspace_id::WorkspaceId;
@@ -1,8 +1,13 @@
+use std::collections::HashSet;
+
 use itertools::Itertools;
-use swayipc::Connection;
+use regex::Regex;
+use swayipc::{Connection, Node};
 
-use crate::config::Config;
-use crate::sway::commands::{get_active_monitor_names, get_assign_and_focus_workspace_command, get_focus_workspace_command, get_workspaces, move_container};
+use crate::cli::ContainerCriteria;
+use crate::config::{Config, MonitorGroup};
+use crate::sway::commands::{assign_workspace_to_monitor, get_active_monitor_names, get_assign_and_focus_workspace_command, get_focus_workspace_command, get_tree, get_workspaces, move_container, move_container_by_id};
+use crate::sway::utils::{find_containers, get_workspace_nodes};
 use super::utils::{find_focused_workspace, get_target_index};
 use super::workspace_id::WorkspaceId;
 
@@ -13,6 +18,7 @@ pub fn move_container_to_workspace_group(
     destination: &str,
     monitor_group: Option<&String>,
     change_focus: bool,
+    criteria: &ContainerCriteria,
 ) {
     let workspaces = get_workspaces(connection);
     let active_monitors = get_active_monitor_names(connection);
@@ -20,22 +26,21 @@ pub fn move_container_to_workspace_group(
     let focused_workspace = find_focused_workspace(&workspaces);
     
     let focused_workspace_id = WorkspaceId::parse_safe(&focused_workspace.name);
-    let (target_monitor_index, default_monitor_group) = focused_workspace_id.map(|id| {
-        (
-            id.get_monitor_index(),
-            id.get_monitor_group_name().to_string(),
-        )
-    }).unwrap_or_else(|| {
+    let (target_monitor_index, default_monitor_group) = match focused_workspace_id {
+        Some(id) => (id.get_monitor_index(), id.get_monitor_group_name().to_string()),
         // focused workspace is not managed, get main monitor of primary group
-        let monitor_group = config.get_primary_group();
-        (
-            monitor_group.get_main_monitor_index(&active_monitors),
-            monitor_group.name.clone(),
-        )
-    });
+        None => match config.get_primary_monitor(&active_monitors) {
+            Some((monitor_group, main_monitor_index)) => (Some(main_monitor_index), monitor_group.name.clone()),
+            None => {
+                eprintln!("None of the configured monitors are active, see `mumowrk config check`");
+                return;
+            },
+        },
+    };
     let target_monitor_group = monitor_group.unwrap_or(&default_monitor_group);
 
-    let target_group_index = get_target_index(&workspaces, target_monitor_group, destination);
+    let mon_group = config.get_group(target_monitor_group).expect("Monitor group not configured");
+    let target_group_index = mon_group.bound_index(get_target_index(&workspaces, target_monitor_group, destination));
 
     let target_workspace_id = WorkspaceId::new(
         target_monitor_group,
@@ -43,17 +48,20 @@ pub fn move_container_to_workspace_group(
         target_group_index,
     );
 
-    move_container(connection, &target_workspace_id);
+    if criteria.is_empty() {
+        move_container(connection, &target_workspace_id);
+    } else {
+        move_matching_containers(connection, mon_group, &active_monitors, target_group_index, criteria);
+    }
 
     if !change_focus {
         return
     };
-    let mon_group = config.get_group(&target_monitor_group).expect("Monitor group not configured");
     let commands = mon_group.monitors.iter()
         .enumerate()
         // skip the monitor that will be in focus for now and non-active monitors
         .filter(|(index, monitor_name)|
-            *index != target_monitor_index
+            Some(*index) != target_monitor_index
             && active_monitors.contains(*monitor_name)
         )
         .map(|(monitor_index, monitor_name)| {
@@ -70,3 +78,90 @@ pub fn move_container_to_workspace_group(
     connection.run_command(commands).expect("Failed to switch workspace groups");
 }
 
+/// Move all containers matching the criteria to the target workspace group.
+/// Containers keep their monitor index if they are already in the target monitor group,
+/// otherwise they are moved to the main monitor of the group.
+fn move_matching_containers(
+    connection: &mut Connection,
+    monitor_group: &MonitorGroup,
+    active_monitors: &HashSet<String>,
+    target_group_index: i32,
+    criteria: &ContainerCriteria,
+) {
+    let matcher = ContainerMatcher::new(criteria);
+    // park the containers if the monitor group has no active monitors
+    let main_monitor_index = monitor_group.get_main_monitor_index(active_monitors);
+
+    let tree = get_tree(connection);
+    let moves: Vec<(i64, WorkspaceId)> = get_workspace_nodes(&tree)
+        .flat_map(|workspace_node| {
+            let monitor_index = workspace_node.name.as_deref()
+                .and_then(WorkspaceId::parse_safe)
+                .filter(|id| id.get_monitor_group_name() == monitor_group.name)
+                .and_then(|id| id.get_monitor_index())
+                .filter(|index| *index < monitor_group.monitors.len())
+                .or(main_monitor_index);
+            find_containers(workspace_node, &|node| matcher.matches(node))
+                .into_iter()
+                .map(move |container| (
+                    container.id,
+                    WorkspaceId::new(&monitor_group.name, monitor_index, target_group_index),
+                ))
+        })
+        .collect();
+
+    if moves.is_empty() {
+        println!("No containers match the criteria. Nothing was moved.");
+    }
+    // Workspaces created by moving a container by criteria would be created on the focused output,
+    // make sure they are created on the correct monitor instead
+    for workspace_id in moves.iter().map(|(_, id)| id).unique_by(|id| id.get_monitor_index()) {
+        let monitor = workspace_id.get_monitor_index()
+            .and_then(|index| monitor_group.monitors.get(index))
+            .filter(|monitor| active_monitors.contains(*monitor));
+        if let Some(monitor) = monitor {
+            assign_workspace_to_monitor(connection, workspace_id, monitor);
+        }
+    }
+    for (container_id, workspace_id) in moves {
+        move_container_by_id(connection, container_id, &workspace_id);
+    }
+}
+
+/// Compiled `ContainerCriteria`, all given criteria have to match
+struct ContainerMatcher {
+    app_id: Option<Regex>,
+    class: Option<Regex>,
+    title: Option<Regex>,
+    con_id: Option<i64>,
+    mark: Option<Regex>,
+}
+
+impl ContainerMatcher {
+    fn new(criteria: &ContainerCriteria) -> Self {
+        let compile = |pattern: &Option<String>| pattern.as_ref().map(|pattern| {
+            Regex::new(pattern).unwrap_or_else(|error| panic!("Invalid criteria '{}': {}", pattern, error))
+        });
+        Self {
+            app_id: compile(&criteria.app_id),
+            class: compile(&criteria.class),
+            title: compile(&criteria.title),
+            con_id: criteria.con_id,
+            mark: compile(&criteria.mark),
+        }
+    }
+
+    fn matches(&self, node: &Node) -> bool {
+        let matches_value = |re: &Option<Regex>, value: Option<&str>| re.as_ref()
+            .map(|re| value.is_some_and(|value| re.is_match(value)))
+            .unwrap_or(true);
+        // Window properties only make sense for views, not for split containers
+        let is_view = node.pid.is_some();
+
+        matches_value(&self.app_id, node.app_id.as_deref())
+            && matches_value(&self.class, node.window_properties.as_ref().and_then(|props| props.class.as_deref()))
+            && matches_value(&self.title, node.name.as_deref().filter(|_| is_view))
+            && self.con_id.map(|id| id == node.id).unwrap_or(true)
+            && self.mark.as_ref().map(|re| node.marks.iter().any(|mark| re.is_match(mark))).unwrap_or(true)
+    }
+}
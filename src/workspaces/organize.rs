@@ -1,52 +1,122 @@
 use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::Write;
 
-use itertools::Itertools;
-use swayipc::{Connection, Workspace};
+use itertools::{Either, Itertools};
+use swayipc::{Connection, Node, Output, Workspace};
 
-use crate::config::{Config, MonitorGroup};
+use crate::cli::ReorganizeStrategy;
+use crate::config::{Config, FallbackPolicy, MonitorGroup, MonitorOrder};
 use crate::sway::commands;
+use crate::state::{DisplacedState, Snapshot};
+use crate::sway::utils::{get_active_monitors, get_output_descriptor, get_workspace_nodes};
 use crate::workspaces::WorkspaceId;
+use super::cleanup::plan_remove_empty_workspace;
+use super::focus::FocusState;
+use super::plan::{execute_plan, plan_container_migration, print_plan, Action, PlannedAction};
+use super::restore::{plan_restore, record_displacements};
 
 
 /// Reorganize all containers and workspaces to match configuration and current
 /// state of monitor configuration.
-pub fn reorganize_everything(connection: &mut Connection, config: &Config) {
-    // TODO:
-    // - save old state
-    // - focus the container that was focused before
-
-    let output_nodes = commands::get_tree(connection).nodes;
-    let _ = File::create(
-        shellexpand::full("~/.config/mumowrk/old_tree.json").unwrap().into_owned()
-    ).map(|mut file| {
-        file.write_all(serde_json::to_string(&output_nodes).unwrap().as_bytes())
-    }).inspect_err(|e| eprintln!("Could not save old tree: {}", e));
-
-    // Reorganize workspace groups for each configured monitor group
-    for monitor_group in &config.groups {
-        println!("Reorganize monitor group {}", monitor_group.name);
-        reorganize_monitor_group(connection, config, monitor_group);
+/// Containers and workspaces displaced by previous reorganizations are restored
+/// first if their original monitor is active again.
+/// Focus and visible workspace groups are restored at the end.
+/// The executed actions are printed if `verbose` is set.
+pub fn reorganize_everything(connection: &mut Connection, config: &Config, scope: &ReorganizeScope, verbose: bool) {
+    let _ = Snapshot::capture(commands::get_tree(connection)).save(config.snapshot_retention)
+        .inspect_err(|e| eprintln!("{}", e));
+
+    let initial_state = ReorganizeState::query(connection);
+    let focus = FocusState::capture(&initial_state);
+
+    let mut displaced = DisplacedState::load();
+    let mut plan = plan_restore(&initial_state, config, scope, &mut displaced);
+    execute_plan(connection, &plan, verbose);
+
+    let state = ReorganizeState::query(connection);
+    let reorganization_plan = plan_reorganization(&state, config, scope);
+    record_displacements(&state, &reorganization_plan, &mut displaced);
+    execute_plan(connection, &reorganization_plan, verbose);
+    displaced.save();
+
+    plan.extend(reorganization_plan);
+    focus.restore(connection, &plan);
+}
+
+/// Print what `reorganize_everything` would do without changing anything.
+/// The reorganization is planned from the state the restoration would leave behind.
+pub fn print_reorganization_plan(connection: &mut Connection, config: &Config, scope: &ReorganizeScope, json: bool) {
+    let mut state = ReorganizeState::query(connection);
+    let mut plan = plan_restore(&state, config, scope, &mut DisplacedState::load());
+    state.apply_plan(&plan);
+    plan.extend(plan_reorganization(&state, config, scope));
+    print_plan(&plan, json);
+}
+
+/// Which workspaces get reorganized and how
+#[derive(Debug, Default)]
+pub struct ReorganizeScope {
+    /// Only reorganize this monitor group, all monitor groups if `None`
+    pub mon_group: Option<String>,
+    /// Only reorganize the workspace group with this index, all workspace groups if `None`
+    pub index: Option<i32>,
+    /// Override the default handling of workspaces whose monitor is inactive
+    pub strategy: Option<ReorganizeStrategy>,
+}
+
+impl ReorganizeScope {
+    /// Whether the workspace falls into this scope
+    pub(super) fn includes(&self, id: &WorkspaceId) -> bool {
+        self.mon_group.as_ref().is_none_or(|group_name| group_name == id.get_monitor_group_name())
+            && self.index.is_none_or(|index| index == id.get_index())
     }
 }
 
-fn reorganize_monitor_group(connection: &mut Connection, config: &Config, monitor_group: &MonitorGroup) {
-    let active_monitors = commands::get_active_monitor_names(connection);
+/// Current state of sway that the reorganization is planned from
+pub(super) struct ReorganizeState {
+    pub active_monitors: HashSet<String>,
+    pub outputs: Vec<Output>,
+    pub workspaces: Vec<Workspace>,
+    pub tree: Node,
+}
+
+impl ReorganizeState {
+    pub(super) fn query(connection: &mut Connection) -> Self {
+        Self {
+            active_monitors: commands::get_active_monitor_names(connection),
+            outputs: get_active_monitors(connection),
+            workspaces: commands::get_workspaces(connection),
+            tree: commands::get_tree(connection),
+        }
+    }
+}
+
+/// Plan the actions needed to reorganize workspace groups for each configured monitor group
+fn plan_reorganization(state: &ReorganizeState, config: &Config, scope: &ReorganizeScope) -> Vec<PlannedAction> {
+    config.groups.iter()
+        .filter(|monitor_group| scope.mon_group.as_ref().is_none_or(|group_name| *group_name == monitor_group.name))
+        .flat_map(|monitor_group| plan_monitor_group(state, config, scope, monitor_group))
+        .collect()
+}
+
+fn plan_monitor_group(
+    state: &ReorganizeState,
+    config: &Config,
+    scope: &ReorganizeScope,
+    monitor_group: &MonitorGroup,
+) -> Vec<PlannedAction> {
     // get indices of active monitors in the monitor group
-    let monitor_indices: HashMap<String, usize> = active_monitors.iter()
+    let monitor_indices: HashMap<String, usize> = state.active_monitors.iter()
         .filter_map(|monitor_name| {
             monitor_group.get_monitor_index(monitor_name).map(|index| (monitor_name.clone(), index))
         })
         .collect();
 
-    let all_workspaces = commands::get_workspaces(connection);
     // group all the workspaces in the monitor group into workspace groups
-    let workspace_groups = all_workspaces.iter()
+    let workspace_groups = state.workspaces.iter()
         .filter_map(|workspace| {
             WorkspaceId::parse_safe(&workspace.name)
                 .and_then(|id| {
-                    if id.get_monitor_group_name() == monitor_group.name {
+                    if id.get_monitor_group_name() == monitor_group.name && scope.includes(&id) {
                         Some((id.get_index(), workspace))
                     } else {
                         None
@@ -55,92 +125,268 @@ fn reorganize_monitor_group(connection: &mut Connection, config: &Config, monito
         })
         .into_group_map();
 
-    for (_, workspaces) in workspace_groups {
-        reorganize_workspace_group(connection, config, monitor_group, &active_monitors, &monitor_indices, &workspaces);
-    }
-}
-
-const FOREIGN_MONITOR_INDEX: usize = 999;
-
-fn get_foreign_monitor<'conf>(config: &'conf Config, active_monitors: &HashSet<String>) -> &'conf String {
-    config.groups.iter()
-        .flat_map(|group| group.monitors.iter())
-        .find(|monitor| active_monitors.contains(*monitor))
-        .expect("No active monitor found for either monitor group!")
+    workspace_groups.into_iter()
+        .sorted_by_key(|(index, _)| *index)
+        .flat_map(|(_, workspaces)| {
+            plan_workspace_group(state, config, scope.strategy, monitor_group, &monitor_indices, &workspaces)
+        })
+        .collect()
 }
 
-fn reorganize_workspace_group(
-    connection: &mut Connection,
+fn plan_workspace_group(
+    state: &ReorganizeState,
     config: &Config,
+    strategy: Option<ReorganizeStrategy>,
     monitor_group: &MonitorGroup,
-    active_monitors: &HashSet<String>,
     monitor_indices: &HashMap<String, usize>,
-    workspaces: &Vec<&Workspace>,
-) {
-    // TODO: if there is an empty workspace on the correct monitor unfocus it to remove it
+    workspaces: &[&Workspace],
+) -> Vec<PlannedAction> {
+    // Empty workspaces that are not visible get removed so that they do not block any monitor
+    let (mut plan, workspaces): (Vec<PlannedAction>, Vec<&Workspace>) = workspaces.iter()
+        .partition_map(|workspace| {
+            match plan_remove_empty_workspace(state, workspace, "it is empty and not visible") {
+                Some(planned_action) => Either::Left(planned_action),
+                None => Either::Right(*workspace),
+            }
+        });
+    if monitor_indices.is_empty() {
+        match (strategy, &monitor_group.fallback) {
+            // the strategy ignores the fallback policy and parks the workspaces
+            (Some(ReorganizeStrategy::SpreadToAvailable), _) => {},
+            // keep the workspaces where they are
+            (Some(ReorganizeStrategy::LeaveInPlace), _) | (_, Some(FallbackPolicy::Leave)) => return plan,
+            (_, Some(FallbackPolicy::MergeInto { index, .. })) => {
+                if let Some(merge_plan) = plan_merge_into(state, config, monitor_group, *index, &workspaces) {
+                    plan.extend(merge_plan);
+                    return plan;
+                }
+                // the target group has no active monitors either, park the workspaces instead
+            },
+            _ => {},
+        }
+    }
+    // Only the default chain and `spread-to-available` use other available monitors
+    let use_available_monitors = matches!(strategy, None | Some(ReorganizeStrategy::SpreadToAvailable));
+    // Output where workspaces get parked if there are no active monitors in this group
+    let fallback_monitor = monitor_indices.is_empty().then(|| {
+        config.get_fallback_monitor(monitor_group, &state.active_monitors)
+            // `spread-to-available` parks the workspaces even with the `leave` policy
+            .or_else(|| config.get_first_active_monitor(&state.active_monitors).cloned())
+            .expect("No active monitor found for either monitor group!")
+    });
     // get a list of available monitors (such monitor that there is no workspace with its index in this group)
     let mut available_monitors = monitor_indices.iter()
         .filter(|(_, index)| {
             !workspaces.iter().any(|workspace| {
                 let id = WorkspaceId::parse(&workspace.name);
-                id.get_monitor_index() == **index
+                id.get_monitor_index() == Some(**index)
             })
         })
-        .sorted_by_key(|(_, index)| **index);
-    let mut used_foreign_monitor = false;
+        .sorted_by_key(|(_, index)| **index)
+        .collect_vec();
+    let mut used_fallback_monitor = false;
 
-    for workspace in workspaces {
+    // Handle already parked workspaces first so that they stay parked
+    for workspace in workspaces.iter().sorted_by_key(|workspace| !WorkspaceId::parse(&workspace.name).is_parked()) {
         let id = WorkspaceId::parse(&workspace.name);
 
-        if monitor_indices.get(&workspace.output) == Some(&id.get_monitor_index()) {
+        if monitor_indices.get(&workspace.output).copied() == id.get_monitor_index() {
             // workspace is on the correct monitor
-            println!("Workspace {} is already on the correct monitor", id);
             continue;
         }
 
         // Maybe it just needs to be reassigned to the correct monitor
         let monitor_candidate_entry = monitor_indices.iter()
-            .find(|(_, index)| **index == id.get_monitor_index())
+            .find(|(_, index)| Some(**index) == id.get_monitor_index())
+            .map(|(name, index)| ((name, Some(*index)), "its monitor is active"))
             // `or` it can go to the next available monitor
-            .or(available_monitors.next())
-            // `or` it can go to a monitor from a different monitor group if there are no active monitors in this group
-            .or((monitor_indices.is_empty() && !used_foreign_monitor).then(|| {
-                used_foreign_monitor = true;
-                (get_foreign_monitor(config, active_monitors), &FOREIGN_MONITOR_INDEX)
+            .or_else(|| use_available_monitors
+                .then(|| take_available_monitor(state, monitor_group, &mut available_monitors, id.get_monitor_index()))
+                .flatten()
+                .map(|(name, index)| ((name, Some(*index)), "its monitor is inactive, nearest available monitor"))
+            )
+            // `or` it can be parked on a fallback monitor if there are no active monitors in this group
+            .or_else(|| fallback_monitor.as_ref().filter(|_| !used_fallback_monitor).map(|monitor| {
+                used_fallback_monitor = true;
+                (
+                    (monitor, None),
+                    "no active monitors in its monitor group, park it on the fallback monitor",
+                )
             }));
-        if let Some((name, index)) = monitor_candidate_entry {
+        if let Some(((name, index), reason)) = monitor_candidate_entry {
             // just move the workspace to the correct or next available monitor
-            commands::move_workspace_to_monitor(connection, &id, name);
-            println!("Move workspace {} to monitor {}", id, name);
-            if *index != id.get_monitor_index() {
+            plan.push(PlannedAction::new(
+                Action::MoveWorkspaceToOutput { workspace: id.to_string(), output: name.clone() },
+                reason,
+            ));
+            if index != id.get_monitor_index() {
                 // rename the workspace if the monitor was not the matching one
-                commands::rename_workspace(connection, &id, &WorkspaceId::new(
-                    id.get_monitor_group_name(),
-                    *index,
-                    id.get_index(),
+                plan.push(PlannedAction::new(
+                    Action::RenameWorkspace {
+                        from: id.to_string(),
+                        to: WorkspaceId::new(id.get_monitor_group_name(), index, id.get_index()).to_string(),
+                    },
+                    match index {
+                        Some(index) => format!("it was moved to a monitor with index {}", index),
+                        None => "it was parked on the fallback monitor".to_string(),
+                    },
                 ));
-                println!("Also rename the workspace to have monitor index {}", index);
             }
             continue;
         }
+        if strategy == Some(ReorganizeStrategy::LeaveInPlace) {
+            // keep the workspace where it is until its monitor is active again
+            continue;
+        }
 
         // All monitors already have a workspace, move all its containers to an existing workspace
-        let main_monitor_index = if monitor_indices.is_empty() {
-            // if using a foreign monitor the workspace there should have been already created above
-            FOREIGN_MONITOR_INDEX
-        } else {
+        let (main_monitor_index, main_monitor) = match &fallback_monitor {
+            // if using a fallback monitor the workspace there should have been already created above
+            Some(monitor) => (None, monitor.clone()),
             // use the main monitor of the monitor group
-            monitor_group.get_main_monitor_index(&monitor_indices.keys().cloned().collect())
+            None => match monitor_group.get_main_monitor_index(&monitor_indices.keys().cloned().collect()) {
+                Some(index) => (Some(index), monitor_group.monitors[index].clone()),
+                None => continue,
+            },
         };
-        let workspace_tree = commands::get_workspace_tree(connection, &id)
-            .expect("Could not find workspace tree for workspace that is supposed to exist");
-        for container in workspace_tree.nodes {
-            commands::move_container_by_id(connection, container.id, &WorkspaceId::new(
-                id.get_monitor_group_name(),
-                main_monitor_index,
-                id.get_index(),
+        let target_id = WorkspaceId::new(id.get_monitor_group_name(), main_monitor_index, id.get_index());
+        plan.extend(plan_move_all_containers(
+            state,
+            config,
+            workspace,
+            &target_id,
+            &format!("no monitor available for workspace {}", id),
+        ));
+        let target_name = target_id.to_string();
+        let target_exists = workspaces.iter().any(|workspace| workspace.name == target_name)
+            || plan.iter().any(|planned_action| matches!(
+                &planned_action.action,
+                Action::MoveWorkspaceToOutput { workspace, .. } | Action::RenameWorkspace { to: workspace, .. }
+                    if *workspace == target_name
+            ));
+        if !target_exists {
+            // the workspace on the main monitor does not exist (or was empty and removed), it gets
+            // created by moving the containers so make sure it ends up on the right monitor
+            plan.push(PlannedAction::new(
+                Action::MoveWorkspaceToOutput { workspace: target_name, output: main_monitor },
+                "move the merged workspace to the main monitor of its monitor group",
             ));
         }
-        println!("Move all containers from workspace {} to monitor {}", id, main_monitor_index);
     }
+
+    plan
+}
+
+/// Take the available monitor for a workspace whose monitor is inactive.
+/// For groups ordered by position this is the monitor nearest to where the inactive
+/// monitor would be relative to the group, otherwise the one with the lowest index.
+/// The inactive monitor is expected between the active monitors with the closest lower
+/// and higher index, comparing centers relative to the group on both axes.
+/// Parked workspaces have no monitor index and get the one with the lowest index.
+fn take_available_monitor<'a>(
+    state: &ReorganizeState,
+    monitor_group: &MonitorGroup,
+    available_monitors: &mut Vec<(&'a String, &'a usize)>,
+    monitor_index: Option<usize>,
+) -> Option<(&'a String, &'a usize)> {
+    if available_monitors.is_empty() {
+        return None;
+    }
+    let Some(monitor_index) = monitor_index.filter(|_| monitor_group.order == MonitorOrder::Position) else {
+        return Some(available_monitors.remove(0));
+    };
+
+    // Centers of the active monitors in the group by their index
+    let centers: Vec<(usize, (i32, i32))> = monitor_group.monitors.iter()
+        .enumerate()
+        .filter_map(|(index, monitor)| {
+            let output = state.outputs.iter()
+                .find(|output| output.name == *monitor || get_output_descriptor(output) == *monitor)?;
+            Some((index, (output.rect.x + output.rect.width / 2, output.rect.y + output.rect.height / 2)))
+        })
+        .collect();
+    if centers.is_empty() {
+        return Some(available_monitors.remove(0));
+    }
+    let (left, right) = centers.iter().map(|(_, (x, _))| *x).minmax().into_option().unwrap();
+    let (top, bottom) = centers.iter().map(|(_, (_, y))| *y).minmax().into_option().unwrap();
+    // Position within the group, 0 is the leftmost (topmost) and 1 the rightmost (bottommost) center
+    let relative_center = |(x, y): (i32, i32)| (
+        (x - left) as f64 / (right - left).max(1) as f64,
+        (y - top) as f64 / (bottom - top).max(1) as f64,
+    );
+    let lower = centers.iter().rev().find(|(index, _)| *index < monitor_index);
+    let higher = centers.iter().find(|(index, _)| *index > monitor_index);
+    let expected_center = match (lower, higher) {
+        (Some((lower_index, lower_center)), Some((higher_index, higher_center))) => {
+            let (lower_center, higher_center) = (relative_center(*lower_center), relative_center(*higher_center));
+            let ratio = (monitor_index - lower_index) as f64 / (higher_index - lower_index) as f64;
+            (
+                lower_center.0 + (higher_center.0 - lower_center.0) * ratio,
+                lower_center.1 + (higher_center.1 - lower_center.1) * ratio,
+            )
+        },
+        (Some((_, center)), None) | (None, Some((_, center))) => relative_center(*center),
+        (None, None) => return Some(available_monitors.remove(0)),
+    };
+
+    let nearest = available_monitors.iter()
+        .position_min_by_key(|(_, index)| {
+            let center = centers.iter()
+                .find(|(candidate, _)| candidate == *index)
+                .map_or(expected_center, |(_, center)| relative_center(*center));
+            let distance = (center.0 - expected_center.0).hypot(center.1 - expected_center.1);
+            // compare in per mille to be able to use integer ordering
+            (distance * 1000.0) as i64
+        })?;
+    Some(available_monitors.remove(nearest))
+}
+
+/// Plan moving all containers of a workspace group of `monitor_group` into workspace group `index`
+/// on the main monitor of another monitor group.
+/// Return `None` if the target monitor group has no active monitors.
+fn plan_merge_into(
+    state: &ReorganizeState,
+    config: &Config,
+    monitor_group: &MonitorGroup,
+    index: i32,
+    workspaces: &[&Workspace],
+) -> Option<Vec<PlannedAction>> {
+    let (target_group, main_monitor_index) = config.get_merge_target(monitor_group, &state.active_monitors)?;
+    let target_id = WorkspaceId::new(&target_group.name, main_monitor_index, index);
+
+    let mut plan: Vec<PlannedAction> = workspaces.iter()
+        .flat_map(|workspace| plan_move_all_containers(
+            state,
+            config,
+            workspace,
+            &target_id,
+            &format!("no active monitors in its monitor group, merge into {}", target_group.name),
+        ))
+        .collect();
+    if !plan.is_empty() && !state.workspaces.iter().any(|workspace| workspace.name == target_id.to_string()) {
+        // the workspace gets created by moving the containers, make sure it ends up on the right monitor
+        plan.push(PlannedAction::new(
+            Action::MoveWorkspaceToOutput {
+                workspace: target_id.to_string(),
+                output: target_group.monitors[main_monitor_index].clone(),
+            },
+            "move the merged workspace to the main monitor of its monitor group",
+        ));
+    }
+    Some(plan)
+}
+
+/// Plan moving all top level containers (tiled and floating) of a workspace to another workspace
+fn plan_move_all_containers(
+    state: &ReorganizeState,
+    config: &Config,
+    workspace: &Workspace,
+    target_id: &WorkspaceId,
+    reason: &str,
+) -> Vec<PlannedAction> {
+    let workspace_node = get_workspace_nodes(&state.tree)
+        .find(|node| node.name.as_ref() == Some(&workspace.name))
+        .expect("Could not find workspace tree for workspace that is supposed to exist");
+    plan_container_migration(workspace_node, &target_id.to_string(), config.merge_layout, reason)
 }
@@ -0,0 +1,176 @@
+use serde::Serialize;
+use swayipc::{Connection, Node, Rect};
+
+use crate::config::MergeLayout;
+use crate::sway::commands;
+use crate::sway::utils::get_workspace_nodes;
+
+
+/// A single step of reorganizing workspaces and containers
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    /// Move a workspace to an output
+    MoveWorkspaceToOutput { workspace: String, output: String },
+    /// Rename a workspace
+    RenameWorkspace { from: String, to: String },
+    /// Move a container to a workspace, floating containers get placed
+    /// at the same relative position and size
+    MoveContainer {
+        con_id: i64,
+        workspace: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        floating: Option<FloatingPlacement>,
+    },
+    /// Move tiled containers to a workspace wrapped in a single container with a layout
+    MoveContainersWrapped { con_ids: Vec<i64>, workspace: String, layout: MergeLayout },
+    /// Remove an empty workspace by showing it and then the workspace visible on its output
+    RemoveWorkspace { workspace: String, visible: String },
+}
+
+/// Position and size of a floating container in percent of its workspace
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct FloatingPlacement {
+    pub x: u8,
+    pub y: u8,
+    pub width: u8,
+    pub height: u8,
+}
+
+impl FloatingPlacement {
+    /// Placement of a container relative to its workspace
+    fn relative_to(rect: &Rect, workspace_rect: &Rect) -> Self {
+        let percent = |value: i32, total: i32| (value * 100 / total.max(1)).clamp(0, 100) as u8;
+        Self {
+            x: percent(rect.x - workspace_rect.x, workspace_rect.width),
+            y: percent(rect.y - workspace_rect.y, workspace_rect.height),
+            width: percent(rect.width, workspace_rect.width),
+            height: percent(rect.height, workspace_rect.height),
+        }
+    }
+}
+
+/// An action together with the reason it is needed
+#[derive(Serialize, Debug, Clone)]
+pub struct PlannedAction {
+    #[serde(flatten)]
+    pub action: Action,
+    pub reason: String,
+}
+
+impl PlannedAction {
+    pub fn new(action: Action, reason: impl Into<String>) -> Self {
+        Self { action, reason: reason.into() }
+    }
+}
+
+impl std::fmt::Display for PlannedAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.action {
+            Action::MoveWorkspaceToOutput { workspace, output } =>
+                write!(f, "Move workspace {} to output {}", workspace, output)?,
+            Action::RenameWorkspace { from, to } =>
+                write!(f, "Rename workspace {} to {}", from, to)?,
+            Action::MoveContainer { con_id, workspace, floating: None } =>
+                write!(f, "Move container {} to workspace {}", con_id, workspace)?,
+            Action::MoveContainer { con_id, workspace, floating: Some(placement) } => write!(
+                f, "Move floating container {} to workspace {} at {}%,{}% sized {}%x{}%",
+                con_id, workspace, placement.x, placement.y, placement.width, placement.height,
+            )?,
+            Action::MoveContainersWrapped { con_ids, workspace, layout } => write!(
+                f, "Move containers {} to workspace {} wrapped in a {:?} container",
+                con_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(", "), workspace, layout,
+            )?,
+            Action::RemoveWorkspace { workspace, .. } =>
+                write!(f, "Remove empty workspace {}", workspace)?,
+        };
+        write!(f, " ({})", self.reason)
+    }
+}
+
+/// Print the planned actions either as human readable lines or as JSON
+pub fn print_plan(plan: &[PlannedAction], json: bool) {
+    if json {
+        println!("{}", serde_json::to_string_pretty(plan).unwrap());
+    } else if plan.is_empty() {
+        println!("Nothing to do");
+    } else {
+        plan.iter().for_each(|action| println!("{}", action));
+    }
+}
+
+/// Run the planned actions in order over the sway IPC connection,
+/// printing each of them first if `verbose` is set
+/// 
+/// # Panics
+/// Panics if any of the commands fail
+pub fn execute_plan(connection: &mut Connection, plan: &[PlannedAction], verbose: bool) {
+    for planned_action in plan {
+        if verbose {
+            println!("{}", planned_action);
+        }
+        match &planned_action.action {
+            Action::MoveWorkspaceToOutput { workspace, output } =>
+                commands::move_workspace_to_monitor(connection, workspace, output),
+            Action::RenameWorkspace { from, to } =>
+                commands::rename_workspace(connection, from, to),
+            Action::MoveContainer { con_id, workspace, floating } => {
+                commands::move_container_by_id(connection, *con_id, workspace);
+                if let Some(placement) = floating {
+                    commands::place_floating_container_by_id(
+                        connection, *con_id, placement.x, placement.y, placement.width, placement.height,
+                    );
+                }
+            },
+            Action::MoveContainersWrapped { con_ids, workspace, layout } =>
+                commands::move_containers_wrapped_by_id(connection, con_ids, workspace, layout.sway_layout()),
+            Action::RemoveWorkspace { workspace, visible } =>
+                commands::remove_empty_workspace(connection, workspace, visible),
+        }
+    }
+}
+
+/// Placement of a top level floating container relative to its workspace so that it can be
+/// placed the same way on another workspace. Return `None` if the container is not floating.
+pub(super) fn get_floating_placement(tree: &Node, con_id: i64) -> Option<FloatingPlacement> {
+    get_workspace_nodes(tree).find_map(|workspace_node| workspace_node.floating_nodes.iter()
+        .find(|container| container.id == con_id)
+        .map(|container| FloatingPlacement::relative_to(&container.rect, &workspace_node.rect)))
+}
+
+/// Plan moving all top level containers of a workspace to another workspace.
+/// Floating containers keep their position and size relative to the workspace,
+/// tiled containers are optionally wrapped in a single container with `layout`.
+pub(super) fn plan_container_migration(
+    workspace_node: &Node,
+    target_workspace: &str,
+    layout: Option<MergeLayout>,
+    reason: &str,
+) -> Vec<PlannedAction> {
+    let tiled_actions = match layout {
+        Some(layout) if !workspace_node.nodes.is_empty() => vec![PlannedAction::new(
+            Action::MoveContainersWrapped {
+                con_ids: workspace_node.nodes.iter().map(|container| container.id).collect(),
+                workspace: target_workspace.to_string(),
+                layout,
+            },
+            reason,
+        )],
+        _ => workspace_node.nodes.iter()
+            .map(|container| PlannedAction::new(
+                Action::MoveContainer { con_id: container.id, workspace: target_workspace.to_string(), floating: None },
+                reason,
+            ))
+            .collect(),
+    };
+    let floating_actions = workspace_node.floating_nodes.iter()
+        .map(|container| PlannedAction::new(
+            Action::MoveContainer {
+                con_id: container.id,
+                workspace: target_workspace.to_string(),
+                floating: Some(FloatingPlacement::relative_to(&container.rect, &workspace_node.rect)),
+            },
+            reason,
+        ));
+    tiled_actions.into_iter().chain(floating_actions).collect()
+}
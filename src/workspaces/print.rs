@@ -1,7 +1,15 @@
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
 use itertools::{Either, Itertools};
 use swayipc::{Connection, EventType};
 
-use crate::sway::commands::get_workspaces;
+use crate::config::{watch_file, Config, FallbackPolicy, StatusFormat};
+use crate::notify::send_error_notification;
+use crate::sway::commands::{get_active_monitor_names, get_workspaces};
+use crate::sway::utils::get_active_monitors;
 
 use super::workspace_id::WorkspaceId;
 
@@ -11,26 +19,105 @@ pub fn get_current_index(connection: &mut Connection, monitor_group: &str) -> St
     let visible_workspace = workspaces.iter()
         .filter(|workspace| workspace.visible)
         .filter_map(|workspace| WorkspaceId::parse_safe(&workspace.name))
-        .filter(|workspace_id| workspace_id.get_monitor_group_name() == monitor_group)
-        .next();
+        .find(|workspace_id| workspace_id.get_monitor_group_name() == monitor_group);
 
     visible_workspace
         .map_or("?".to_string(), |workspace_id| workspace_id.get_index().to_string())
 }
 
-pub fn subscribe_and_print(connection: &mut Connection, printer: fn(&mut Connection)) {
+/// What happened that the state should be printed again
+#[derive(PartialEq)]
+enum Update {
+    Workspace,
+    ConfigChanged,
+}
+
+/// Print the state and keep printing it whenever workspaces change.
+/// The config file and its includes are watched and reloaded when they change. An invalid config is reported
+/// with a notification and the previous one is kept, workspaces are reorganized
+/// if the new config assigns monitors to monitor groups differently.
+pub fn subscribe_and_print(
+    connection: &mut Connection,
+    mut config: Config,
+    config_path: &str,
+    profile: Option<&str>,
+    printer: fn(&mut Connection, &Config),
+) {
     // Print initial state
-    printer(connection);
+    printer(connection, &config);
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watched_files = Vec::new();
+    watch_config_files(&config, &mut watched_files, &sender);
     // Open a new connection to listen for events
     let listen_connection = Connection::new().expect("Failed to connect to swayipc");
     let events = listen_connection.subscribe([EventType::Workspace]).expect("Failed to subscribe to workspace events");
+    let workspace_sender = sender.clone();
+    thread::spawn(move || {
+        for _ in events {
+            if workspace_sender.send(Update::Workspace).is_err() {
+                break;
+            }
+        }
+    });
+
     // Print updates as events come
-    for _ in events {
-        printer(connection);
+    for update in receiver {
+        if update == Update::ConfigChanged {
+            match Config::load_for_outputs(config_path, profile, &get_active_monitors(connection)) {
+                Ok(new_config) => {
+                    let membership_changed = new_config.get_membership() != config.get_membership();
+                    config = new_config;
+                    watch_config_files(&config, &mut watched_files, &sender);
+                    if membership_changed {
+                        reorganize_in_background(config_path, profile);
+                    }
+                },
+                Err(error) => {
+                    eprintln!("{}", error);
+                    send_error_notification(connection, &format!("{}\nKeeping the previous config", error));
+                },
+            }
+        }
+        printer(connection, &config);
+    }
+}
+
+/// Watch the files of the config, and its includes, that are not watched yet
+fn watch_config_files(config: &Config, watched_files: &mut Vec<PathBuf>, sender: &Sender<Update>) {
+    for file in config.files.iter().filter(|file| !watched_files.contains(file)).collect::<Vec<_>>() {
+        let config_sender = sender.clone();
+        watch_file(file, move || {
+            let _ = config_sender.send(Update::ConfigChanged);
+        });
+        watched_files.push(file.clone());
+    }
+}
+
+/// Start `mumowrk reorganize` as a separate process so that its output does not get
+/// mixed with the printed state. It is waited for in another thread to not block printing.
+fn reorganize_in_background(config_path: &str, profile: Option<&str>) {
+    let executable = std::env::current_exe().expect("Failed to find the mumowrk executable");
+    let mut command = Command::new(executable);
+    command.args(["--config", config_path]);
+    if let Some(profile) = profile {
+        command.args(["--profile", profile]);
     }
+    let mut child = match command.arg("reorganize").stdout(Stdio::null()).spawn() {
+        Ok(child) => child,
+        Err(error) => {
+            eprintln!("Failed to run reorganize after config change: {}", error);
+            return;
+        },
+    };
+    thread::spawn(move || match child.wait() {
+        Ok(status) if !status.success() => eprintln!("Reorganize after config change failed: {}", status),
+        Ok(_) => {},
+        Err(error) => eprintln!("Failed to wait for reorganize after config change: {}", error),
+    });
 }
 
-pub fn print_state_plain(connection: &mut Connection) {
+pub fn print_state_plain(connection: &mut Connection, config: &Config) {
     let simple_formatters = StateFormatters {
         unmanaged: StateFormattersUnmanaged {
             focused: |name| format!("*{}*", name),
@@ -47,6 +134,7 @@ pub fn print_state_plain(connection: &mut Connection) {
             group_name: StateFormattersManagedGroupName {
                 focused: |name| format!("*{}*", name),
                 unfocused: |name| name.to_string(),
+                fallback: |name, state| format!("{} ({})", name, state),
             },
             mon_group: StateFormattersManagedMonGroup {
                 whole: |mon_group, workspace_groups| format!("{}: {}", mon_group, workspace_groups),
@@ -57,7 +145,7 @@ pub fn print_state_plain(connection: &mut Connection) {
         separator: " | ".to_string(),
     };
 
-    println!("{}", format_state(get_state(connection), &simple_formatters));
+    println!("{}", format_state(get_state(connection, config), &simple_formatters));
 }
 
 /// What a waybar module expects to see
@@ -68,8 +156,8 @@ struct ModuleInput {
     class: Option<String>,
 }
 
-pub fn print_waybar_module(connection: &mut Connection) {
-    let display_text = get_state_rich_text(connection);
+pub fn print_waybar_module(connection: &mut Connection, config: &Config) {
+    let display_text = get_state_rich_text(connection, config);
     let output = ModuleInput {
         text: display_text,
         class: Some("mumowrk".to_string()),
@@ -79,7 +167,7 @@ pub fn print_waybar_module(connection: &mut Connection) {
 }
 
 /// Return a fromatted string with Pango markup representing the current state
-pub fn get_state_rich_text(connection: &mut Connection) -> String {
+pub fn get_state_rich_text(connection: &mut Connection, config: &Config) -> String {
     let formatters = StateFormatters {
         unmanaged: StateFormattersUnmanaged {
             focused: |name| format!("<u><b>{}</b></u>", name),
@@ -96,6 +184,7 @@ pub fn get_state_rich_text(connection: &mut Connection) -> String {
             group_name: StateFormattersManagedGroupName {
                 focused: |name| format!("<u>{}</u>", name),
                 unfocused: |name| name.to_string(),
+                fallback: |name, state| format!("{} <i>({})</i>", name, state),
             },
             mon_group: StateFormattersManagedMonGroup {
                 whole: |mon_group, workspace_groups| format!("{}: {}", mon_group, workspace_groups),
@@ -106,11 +195,13 @@ pub fn get_state_rich_text(connection: &mut Connection) -> String {
         separator: "".to_string(),
     };
 
-    format_state(get_state(connection), &formatters)
+    format_state(get_state(connection, config), &formatters)
 }
 
 
-type WorkspacesState = (Vec<(String, Vec<(i32, bool)>, bool)>, Vec<(String, bool)>);
+/// Managed monitor groups with their workspace groups, focus and fallback state,
+/// and unmanaged workspaces
+type WorkspacesState = (Vec<(String, Vec<(i32, bool)>, bool, Option<&'static str>)>, Vec<(String, bool)>);
 
 #[derive(Debug)]
 struct StateFormatters {
@@ -142,6 +233,8 @@ struct StateFormattersManagedWorkspaces {
 struct StateFormattersManagedGroupName {
     focused: fn(&str) -> String,
     unfocused: fn(&str) -> String,
+    /// Applied on top of the label when the monitor group has no active monitors
+    fallback: fn(&str, &str) -> String,
 }
 #[derive(Debug)]
 struct StateFormattersManagedMonGroup {
@@ -162,27 +255,29 @@ fn format_state(
         ).join(&unmngd_fmts.separator);
 
     let managed_groups_str = managed.iter()
-        .map(|(mon_group, workspace_groups, focused)| {
+        .map(|(mon_group, workspace_groups, focused, fallback_state)| {
             let workspace_groups_str = workspace_groups.iter()
                 .map(|(index, focused)|
                     (if *focused { mngd_fmts.workspaces.focused } else { mngd_fmts.workspaces.unfocused })(*index)
                 ).join(&mngd_fmts.workspaces.separator);
-            let mon_group_label = (if *focused { mngd_fmts.group_name.focused } else { mngd_fmts.group_name.unfocused })(mon_group);
+            let mut mon_group_label = (if *focused { mngd_fmts.group_name.focused } else { mngd_fmts.group_name.unfocused })(mon_group);
+            if let Some(state) = fallback_state {
+                mon_group_label = (mngd_fmts.group_name.fallback)(&mon_group_label, state);
+            }
             (mngd_fmts.mon_group.whole)(&mon_group_label, &workspace_groups_str)
         })
         .join(&mngd_fmts.mon_group.separator);
 
-    let output_text = (
+    (
         if unmanaged_workspaces_str.is_empty() { "".to_string() }
         else { (unmngd_fmts.whole)(&unmanaged_workspaces_str) + &formatters.separator }
-    ) + &(mngd_fmts.whole)(&managed_groups_str);
-
-    output_text
+    ) + &(mngd_fmts.whole)(&managed_groups_str)
 }
 
 /// Get current workspaces and process the state into workspace groups state
-fn get_state(connection: &mut Connection) -> WorkspacesState {
+fn get_state(connection: &mut Connection, config: &Config) -> WorkspacesState {
     let workspaces = get_workspaces(connection);
+    let active_monitors = get_active_monitor_names(connection);
     
     let (unmanaged_workspaces, managed_ids): (Vec<_>, Vec<_>) =
         workspaces.iter().partition_map(|workspace| {
@@ -198,21 +293,32 @@ fn get_state(connection: &mut Connection) -> WorkspacesState {
     let workspaces_by_monitor_groups =
         managed_ids.iter().into_group_map_by(|(id, _)| id.get_monitor_group_name());
     let monitor_groups = workspaces_by_monitor_groups.iter()
-        .map(|(group, workspaces)| {
+        .filter_map(|(group, workspaces)| {
+            let status_format = config.get_group(group)
+                .map(|group_config| group_config.get_status_format())
+                .unwrap_or_default();
             let workspace_groups = workspaces.iter()
+                .filter(|(_, visible)| status_format != StatusFormat::Current || *visible)
                 .unique_by(|(id, _)| id.get_index())
                 .sorted_by_key(|(id, _)| id.get_index())
                 .map(|(id, visible)| (id.get_index(), *visible))
                 .collect();
-            (*group, workspace_groups)
+            (status_format != StatusFormat::Hidden).then_some((*group, workspace_groups))
         })
         .sorted_by_key(|(group, _)| *group)
         .map(|(group, workspace_groups)| {
-            let group_is_focused = focused_workspace_id.clone().map_or(
-                false,
+            let group_is_focused = focused_workspace_id.clone().is_some_and(
                 |focused_id| focused_id.get_monitor_group_name() == group,
             );
-            (group.to_string(), workspace_groups, group_is_focused)
+            // Monitor groups without active monitors are in a fallback state
+            let fallback_state = config.get_group(group)
+                .filter(|group_config| !group_config.has_active_monitor(&active_monitors))
+                .map(|group_config| match group_config.fallback {
+                    Some(FallbackPolicy::Leave) => "inactive",
+                    Some(FallbackPolicy::MergeInto { .. }) => "merged",
+                    _ => "parked",
+                });
+            (group.to_string(), workspace_groups, group_is_focused, fallback_state)
         }
         ).collect_vec();
 
@@ -0,0 +1,151 @@
+use std::collections::{HashMap, HashSet};
+
+use swayipc::Node;
+
+use crate::config::Config;
+use crate::state::DisplacedState;
+use crate::sway::utils::get_workspace_nodes;
+use super::organize::{ReorganizeScope, ReorganizeState};
+use super::plan::{get_floating_placement, Action, PlannedAction};
+use super::workspace_id::WorkspaceId;
+
+
+/// Plan moving displaced workspaces and containers back to their original monitor index
+/// if that monitor is active again. Restored records and records of workspaces
+/// or containers that do not exist anymore are removed from `displaced`.
+/// Records whose original workspace is outside of `scope` are kept for later.
+pub(super) fn plan_restore(
+    state: &ReorganizeState,
+    config: &Config,
+    scope: &ReorganizeScope,
+    displaced: &mut DisplacedState,
+) -> Vec<PlannedAction> {
+    let existing_workspaces: HashSet<&str> = state.workspaces.iter()
+        .map(|workspace| workspace.name.as_str())
+        .collect();
+    let container_workspaces = get_container_workspaces(&state.tree);
+    // Return the original monitor of a workspace if it is in scope and active
+    let active_original_monitor = |workspace_name: &str| -> Option<String> {
+        let id = WorkspaceId::parse_safe(workspace_name).filter(|id| scope.includes(id))?;
+        let monitor = config.get_group(id.get_monitor_group_name())?
+            .monitors.get(id.get_monitor_index()?)?;
+        state.active_monitors.contains(monitor).then(|| monitor.clone())
+    };
+
+    let mut plan = Vec::new();
+    // Workspaces that have to be moved to their original monitor at the end
+    let mut restored_workspaces: Vec<(String, String)> = Vec::new();
+
+    displaced.workspaces.retain(|current, original| {
+        if !existing_workspaces.contains(current.as_str()) {
+            return false;
+        }
+        let Some(monitor) = active_original_monitor(original) else {
+            return true;
+        };
+        if existing_workspaces.contains(original.as_str()) {
+            // The original workspace was created again in the meantime, merge into it
+            container_workspaces.iter()
+                .filter(|(_, (workspace, top_level))| workspace == current && *top_level)
+                .for_each(|(con_id, _)| plan.push(PlannedAction::new(
+                    Action::MoveContainer {
+                        con_id: *con_id,
+                        workspace: original.clone(),
+                        floating: get_floating_placement(&state.tree, *con_id),
+                    },
+                    format!("restore workspace {} merged into {}", original, current),
+                )));
+        } else {
+            plan.push(PlannedAction::new(
+                Action::RenameWorkspace { from: current.clone(), to: original.clone() },
+                format!("monitor {} is active again", monitor),
+            ));
+        }
+        restored_workspaces.push((original.clone(), monitor));
+        false
+    });
+
+    displaced.containers.retain(|con_id, original| {
+        let Some((current, _)) = container_workspaces.get(con_id) else {
+            return false;
+        };
+        let Some(monitor) = active_original_monitor(original) else {
+            return true;
+        };
+        if current != original {
+            plan.push(PlannedAction::new(
+                Action::MoveContainer {
+                    con_id: *con_id,
+                    workspace: original.clone(),
+                    floating: get_floating_placement(&state.tree, *con_id),
+                },
+                format!("monitor {} is active again", monitor),
+            ));
+        }
+        if !existing_workspaces.contains(original.as_str())
+            && !restored_workspaces.iter().any(|(workspace, _)| workspace == original)
+        {
+            restored_workspaces.push((original.clone(), monitor));
+        }
+        false
+    });
+
+    plan.extend(restored_workspaces.into_iter().map(|(workspace, output)| PlannedAction::new(
+        Action::MoveWorkspaceToOutput { workspace, output },
+        "move restored workspace to its original monitor",
+    )));
+    plan
+}
+
+/// Record workspaces renamed and containers moved away from their workspace by `plan`
+/// so that `plan_restore` can return them later.
+pub(super) fn record_displacements(state: &ReorganizeState, plan: &[PlannedAction], displaced: &mut DisplacedState) {
+    let container_workspaces = get_container_workspaces(&state.tree);
+    for planned_action in plan {
+        match &planned_action.action {
+            Action::RenameWorkspace { from, to } => {
+                let original = displaced.workspaces.remove(from).unwrap_or(from.clone());
+                if &original != to {
+                    displaced.workspaces.insert(to.clone(), original);
+                }
+            },
+            Action::MoveContainer { con_id, .. } => {
+                record_container(displaced, &container_workspaces, *con_id);
+            },
+            Action::MoveContainersWrapped { con_ids, .. } => {
+                for con_id in con_ids {
+                    record_container(displaced, &container_workspaces, *con_id);
+                }
+            },
+            Action::MoveWorkspaceToOutput { .. } | Action::RemoveWorkspace { .. } => {},
+        }
+    }
+}
+
+/// Record the original workspace of a container unless it is already recorded
+fn record_container(displaced: &mut DisplacedState, container_workspaces: &HashMap<i64, (String, bool)>, con_id: i64) {
+    let Some((current, _)) = container_workspaces.get(&con_id) else {
+        return;
+    };
+    let original = displaced.original_workspace(current).to_string();
+    displaced.containers.entry(con_id).or_insert(original);
+}
+
+/// Map every container ID to the name of its workspace
+/// and whether it is a direct child of the workspace
+fn get_container_workspaces(tree: &Node) -> HashMap<i64, (String, bool)> {
+    fn collect(node: &Node, workspace: &str, top_level: bool, map: &mut HashMap<i64, (String, bool)>) {
+        for child in node.nodes.iter().chain(node.floating_nodes.iter()) {
+            map.insert(child.id, (workspace.to_string(), top_level));
+            collect(child, workspace, false, map);
+        }
+    }
+
+    let mut map = HashMap::new();
+    for workspace_node in get_workspace_nodes(tree) {
+        if let Some(name) = &workspace_node.name {
+            collect(workspace_node, name, true, &mut map);
+        }
+    }
+    map
+}
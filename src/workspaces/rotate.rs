@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+use swayipc::Connection;
+
+use crate::config::MonitorGroup;
+use crate::sway::commands::{get_active_monitor_names, get_focus_workspace_command, get_move_workspace_to_monitor_command, get_workspaces};
+use super::utils::{find_focused_workspace, get_target_index, rename_workspaces};
+use super::workspace_id::WorkspaceId;
+
+
+/// Rotate workspaces of a workspace group across the active monitors of the monitor group.
+/// Content of each monitor moves to the next one, the last one wraps around to the first.
+pub fn rotate_workspace_group(
+    connection: &mut Connection,
+    monitor_group: &MonitorGroup,
+    index: Option<&String>,
+    reverse: bool,
+) {
+    let active_monitors = get_active_monitor_names(connection);
+    let monitor_indices: Vec<usize> = monitor_group.monitors.iter()
+        .enumerate()
+        .filter(|(_, monitor)| active_monitors.contains(*monitor))
+        .map(|(monitor_index, _)| monitor_index)
+        .collect();
+    if monitor_indices.len() < 2 {
+        println!("Monitor group {} has less than two active monitors. Nothing was moved.", monitor_group.name);
+        return;
+    }
+    let shift = if reverse { monitor_indices.len() - 1 } else { 1 };
+    let mapping = monitor_indices.iter()
+        .enumerate()
+        .map(|(position, monitor_index)| (
+            *monitor_index,
+            monitor_indices[(position + shift) % monitor_indices.len()],
+        ))
+        .collect();
+
+    permute_monitors(connection, monitor_group, index, &mapping);
+}
+
+/// Exchange workspaces of a workspace group between two monitors of the monitor group
+pub fn swap_monitors(
+    connection: &mut Connection,
+    monitor_group: &MonitorGroup,
+    index: Option<&String>,
+    monitor_a: usize,
+    monitor_b: usize,
+) {
+    let active_monitors = get_active_monitor_names(connection);
+    let is_active = |monitor_index: usize| monitor_group.monitors.get(monitor_index)
+        .is_some_and(|monitor| active_monitors.contains(monitor));
+    if !is_active(monitor_a) || !is_active(monitor_b) {
+        eprintln!("Both monitors have to be active monitors of the monitor group {}", monitor_group.name);
+        return;
+    }
+
+    let mapping = HashMap::from([(monitor_a, monitor_b), (monitor_b, monitor_a)]);
+    permute_monitors(connection, monitor_group, index, &mapping);
+}
+
+/// Rename workspaces of the workspace group to have their monitor index mapped by `mapping`
+/// and move them to the matching outputs. The workspace group is shown on all monitors
+/// afterwards and focus stays on the same monitor.
+fn permute_monitors(
+    connection: &mut Connection,
+    monitor_group: &MonitorGroup,
+    index: Option<&String>,
+    mapping: &HashMap<usize, usize>,
+) {
+    let workspaces = get_workspaces(connection);
+    // Default to the currently visible workspace group
+    let workspace_group_index = get_target_index(
+        &workspaces,
+        &monitor_group.name,
+        index.map(String::as_str).unwrap_or("+0"),
+    );
+
+    let renames: Vec<(WorkspaceId, WorkspaceId)> = workspaces.iter()
+        .filter_map(|workspace| WorkspaceId::parse_safe(&workspace.name))
+        .filter(|id| id.get_monitor_group_name() == monitor_group.name && id.get_index() == workspace_group_index)
+        .filter_map(|id| {
+            let monitor_index = id.get_monitor_index()?;
+            let new_monitor_index = *mapping.get(&monitor_index)?;
+            (new_monitor_index != monitor_index).then(|| {
+                let new_id = WorkspaceId::new(&monitor_group.name, new_monitor_index, workspace_group_index);
+                (id, new_id)
+            })
+        })
+        .collect();
+    if renames.is_empty() {
+        println!("None of the workspaces exist. Nothing was moved.");
+        return;
+    }
+
+    let focused_workspace = find_focused_workspace(&workspaces);
+    let next_focus = WorkspaceId::parse_safe(&focused_workspace.name)
+        .filter(|id| id.get_monitor_group_name() == monitor_group.name && id.get_index() == workspace_group_index)
+        // keep the focus on the same monitor
+        .map(|id| id.to_string())
+        .unwrap_or(focused_workspace.name.clone());
+
+    rename_workspaces(connection, &renames);
+
+    let commands = renames.iter()
+        .filter_map(|(_, new_id)| Some(get_move_workspace_to_monitor_command(
+            new_id,
+            monitor_group.monitors.get(new_id.get_monitor_index()?)?,
+        )))
+        // Show all moved workspaces and restore focus at the end
+        .chain(renames.iter().map(|(_, new_id)| get_focus_workspace_command(new_id)))
+        .chain([get_focus_workspace_command(&next_focus)])
+        .join(";");
+    connection.run_command(commands).expect("Failed to move workspaces to monitors");
+}
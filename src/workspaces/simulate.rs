@@ -0,0 +1,126 @@
+use swayipc::Node;
+
+use crate::sway::utils::{get_output_descriptor, get_workspace_nodes};
+use super::organize::ReorganizeState;
+use super::plan::{Action, PlannedAction};
+
+
+impl ReorganizeState {
+    /// Change the state the way executing `plan` would change sway, as far as planning
+    /// depends on it, so that a later phase can be planned without executing the plan.
+    /// Workspaces created by the plan are not focused nor visible.
+    pub(super) fn apply_plan(&mut self, plan: &[PlannedAction]) {
+        for planned_action in plan {
+            match &planned_action.action {
+                Action::MoveWorkspaceToOutput { workspace, output } => {
+                    let output_name = self.outputs.iter()
+                        .find(|candidate| candidate.name == *output || get_output_descriptor(candidate) == *output)
+                        .map_or(output.clone(), |candidate| candidate.name.clone());
+                    self.ensure_workspace(workspace, &output_name);
+                    self.move_workspace(workspace, &output_name);
+                },
+                Action::RenameWorkspace { from, to } => {
+                    self.workspaces.iter_mut()
+                        .filter(|candidate| candidate.name == *from)
+                        .for_each(|candidate| candidate.name = to.clone());
+                    if let Some(node) = find_workspace_node_mut(&mut self.tree, from) {
+                        node.name = Some(to.clone());
+                    }
+                },
+                Action::MoveContainer { con_id, workspace, .. } =>
+                    self.move_containers(&[*con_id], workspace),
+                Action::MoveContainersWrapped { con_ids, workspace, .. } =>
+                    self.move_containers(con_ids, workspace),
+                Action::RemoveWorkspace { workspace, .. } => {
+                    self.workspaces.retain(|candidate| candidate.name != *workspace);
+                    for output_node in self.tree.nodes.iter_mut() {
+                        output_node.nodes.retain(|node| node.name.as_ref() != Some(workspace));
+                    }
+                },
+            }
+        }
+    }
+
+    /// Add a workspace on the output unless it exists, like sway creates it when it is used
+    fn ensure_workspace(&mut self, workspace: &str, output: &str) {
+        if self.workspaces.iter().any(|candidate| candidate.name == workspace) {
+            return;
+        }
+        let Some(mut new_workspace) = self.workspaces.first().cloned() else {
+            return;
+        };
+        new_workspace.name = workspace.to_string();
+        new_workspace.output = output.to_string();
+        new_workspace.visible = false;
+        new_workspace.focused = false;
+        self.workspaces.push(new_workspace);
+
+        let Some(mut new_node) = get_workspace_nodes(&self.tree).next().cloned() else {
+            return;
+        };
+        new_node.name = Some(workspace.to_string());
+        new_node.nodes.clear();
+        new_node.floating_nodes.clear();
+        new_node.focused = false;
+        if let Some(output_node) = self.tree.nodes.iter_mut().find(|node| node.name.as_deref() == Some(output)) {
+            output_node.nodes.push(new_node);
+        }
+    }
+
+    fn move_workspace(&mut self, workspace: &str, output: &str) {
+        self.workspaces.iter_mut()
+            .filter(|candidate| candidate.name == workspace)
+            .for_each(|candidate| candidate.output = output.to_string());
+        let mut moved_nodes = Vec::new();
+        for output_node in self.tree.nodes.iter_mut() {
+            let (moved, kept): (Vec<Node>, Vec<Node>) = output_node.nodes.drain(..)
+                .partition(|node| node.name.as_deref() == Some(workspace));
+            output_node.nodes = kept;
+            moved_nodes.extend(moved);
+        }
+        if let Some(output_node) = self.tree.nodes.iter_mut().find(|node| node.name.as_deref() == Some(output)) {
+            output_node.nodes.extend(moved_nodes);
+        }
+    }
+
+    /// Move containers to a workspace, a missing workspace is created on the focused output
+    fn move_containers(&mut self, con_ids: &[i64], workspace: &str) {
+        let focused_output = self.workspaces.iter()
+            .find(|candidate| candidate.focused)
+            .map(|candidate| candidate.output.clone())
+            .unwrap_or_default();
+        self.ensure_workspace(workspace, &focused_output);
+        for con_id in con_ids {
+            let Some((container, floating)) = take_container(&mut self.tree, *con_id) else {
+                continue;
+            };
+            let Some(workspace_node) = find_workspace_node_mut(&mut self.tree, workspace) else {
+                continue;
+            };
+            if floating {
+                workspace_node.floating_nodes.push(container);
+            } else {
+                workspace_node.nodes.push(container);
+            }
+        }
+    }
+}
+
+fn find_workspace_node_mut<'tree>(tree: &'tree mut Node, workspace: &str) -> Option<&'tree mut Node> {
+    tree.nodes.iter_mut()
+        .flat_map(|output_node| output_node.nodes.iter_mut())
+        .find(|node| node.name.as_deref() == Some(workspace))
+}
+
+/// Remove a container from the tree, return it and whether it was floating
+fn take_container(node: &mut Node, con_id: i64) -> Option<(Node, bool)> {
+    if let Some(position) = node.nodes.iter().position(|child| child.id == con_id) {
+        return Some((node.nodes.remove(position), false));
+    }
+    if let Some(position) = node.floating_nodes.iter().position(|child| child.id == con_id) {
+        return Some((node.floating_nodes.remove(position), true));
+    }
+    node.nodes.iter_mut()
+        .chain(node.floating_nodes.iter_mut())
+        .find_map(|child| take_container(child, con_id))
+}
@@ -0,0 +1,158 @@
+use std::collections::{HashMap, HashSet};
+
+use itertools::Itertools;
+use swayipc::{Connection, Node};
+
+use crate::config::Config;
+use crate::state::Snapshot;
+use crate::sway::commands::{get_active_outputs, get_tree, get_workspaces};
+use crate::sway::utils::{find_containers, get_workspace_nodes};
+use super::plan::{execute_plan, get_floating_placement, Action, PlannedAction};
+
+
+/// Save a snapshot of the current tree
+pub fn save_snapshot(connection: &mut Connection, config: &Config) -> Result<Snapshot, String> {
+    let snapshot = Snapshot::capture(get_tree(connection));
+    snapshot.save(config.snapshot_retention)?;
+    Ok(snapshot)
+}
+
+/// Print saved snapshots from the newest to the oldest
+pub fn print_snapshot_list() {
+    let ids = Snapshot::list_ids();
+    if ids.is_empty() {
+        println!("No snapshots saved");
+    }
+    for id in ids.into_iter().rev() {
+        match Snapshot::load(id) {
+            Ok(snapshot) => println!(
+                "{}  {}  {} windows",
+                snapshot.id,
+                snapshot.format_time(),
+                snapshot.windows.len(),
+            ),
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+}
+
+/// Print recorded windows of a snapshot grouped by their workspace,
+/// the latest snapshot is used if `id` is not given
+pub fn print_snapshot(id: Option<u128>) {
+    let Some(id) = id.or(Snapshot::list_ids().last().copied()) else {
+        eprintln!("No snapshots saved");
+        return;
+    };
+    let snapshot = match Snapshot::load(id) {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        },
+    };
+
+    println!("Snapshot {} from {}", snapshot.id, snapshot.format_time());
+    for (workspace, windows) in snapshot.windows.iter()
+        .into_group_map_by(|window| window.workspace.as_str())
+        .into_iter()
+        .sorted_by_key(|(workspace, _)| *workspace)
+    {
+        println!("{}:", workspace);
+        for window in windows {
+            println!(
+                "  [{}] {} - {}",
+                window.con_id,
+                window.app_id.as_deref().unwrap_or("?"),
+                window.title.as_deref().unwrap_or(""),
+            );
+        }
+    }
+}
+
+/// Move existing windows back to the workspaces recorded in the snapshot and move
+/// those workspaces to the outputs recorded in the snapshot if they are active.
+/// Windows are matched by container ID first and by app ID and title if the container
+/// does not exist anymore.
+pub fn restore_snapshot(connection: &mut Connection, id: u128) {
+    let snapshot = match Snapshot::load(id) {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        },
+    };
+    let tree = get_tree(connection);
+    // Current windows and their workspaces
+    let current_windows: Vec<(&Node, &str)> = get_workspace_nodes(&tree)
+        .flat_map(|workspace_node| {
+            let workspace = workspace_node.name.as_deref().unwrap_or_default();
+            find_containers(workspace_node, &|node| node.pid.is_some())
+                .into_iter()
+                .map(move |node| (node, workspace))
+        })
+        .collect();
+    let window_app_id = |node: &Node| node.app_id.clone()
+        .or_else(|| node.window_properties.as_ref().and_then(|props| props.class.clone()));
+
+    // Windows matched by ID have priority over windows matched by app ID and title
+    let mut used_windows: HashSet<i64> = snapshot.windows.iter()
+        .filter(|window| current_windows.iter().any(|(node, _)| node.id == window.con_id))
+        .map(|window| window.con_id)
+        .collect();
+    let mut plan = Vec::new();
+    for window in &snapshot.windows {
+        let current = current_windows.iter()
+            .find(|(node, _)| node.id == window.con_id)
+            .or_else(|| current_windows.iter().find(|(node, _)| {
+                !used_windows.contains(&node.id)
+                    && window_app_id(node) == window.app_id
+                    && node.name == window.title
+            }));
+        let Some((node, workspace)) = current else {
+            continue;
+        };
+        used_windows.insert(node.id);
+        if *workspace != window.workspace {
+            plan.push(PlannedAction::new(
+                Action::MoveContainer {
+                    con_id: node.id,
+                    workspace: window.workspace.clone(),
+                    floating: get_floating_placement(&tree, node.id),
+                },
+                format!("restore snapshot {}", snapshot.id),
+            ));
+        }
+    }
+
+    // Make sure the workspaces end up on their recorded outputs
+    let recorded_outputs: HashMap<&str, &str> = snapshot.tree.nodes.iter()
+        .flat_map(|output_node| output_node.nodes.iter()
+            .filter_map(move |workspace_node| Some((workspace_node.name.as_deref()?, output_node.name.as_deref()?))))
+        .collect();
+    let active_outputs = get_active_outputs(connection);
+    let workspace_outputs: HashMap<String, String> = get_workspaces(connection).into_iter()
+        .map(|workspace| (workspace.name, workspace.output))
+        .collect();
+    let target_workspaces = plan.iter()
+        .filter_map(|planned_action| match &planned_action.action {
+            Action::MoveContainer { workspace, .. } => Some(workspace.clone()),
+            _ => None,
+        })
+        .unique()
+        .collect_vec();
+    for workspace in target_workspaces {
+        let Some(output) = recorded_outputs.get(workspace.as_str())
+            .filter(|output| active_outputs.iter().any(|(name, _)| name == **output))
+        else {
+            continue;
+        };
+        if workspace_outputs.get(&workspace).map(String::as_str) != Some(*output) {
+            plan.push(PlannedAction::new(
+                Action::MoveWorkspaceToOutput { workspace, output: output.to_string() },
+                format!("restore snapshot {}", snapshot.id),
+            ));
+        }
+    }
+
+    execute_plan(connection, &plan, false);
+}
@@ -0,0 +1,84 @@
+use std::collections::HashSet;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use swayipc::{Connection, Event, EventType, WindowChange};
+
+use crate::config::Config;
+use crate::sway::commands::{exec_command, get_assign_and_focus_workspace_command, move_container_by_id};
+use super::workspace_id::WorkspaceId;
+
+
+/// How long to wait for windows of startup commands
+const WINDOW_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Execute the startup commands of all monitor groups, each one on its workspace
+/// focused on its monitor. Wait for the windows of commands with an `app_id`
+/// and move them to their workspaces.
+///
+/// # Panics
+/// Panics if a sway command or the subscription to window events fails
+pub fn run_startup_commands(connection: &mut Connection, config: &Config, active_monitors: &HashSet<String>) {
+    let mut launches = Vec::new();
+    for group in config.groups.iter() {
+        for startup in group.startup.iter() {
+            match group.monitors.get(startup.monitor).filter(|monitor| active_monitors.contains(*monitor)) {
+                Some(monitor) => launches.push((WorkspaceId::new(&group.name, startup.monitor, startup.index), monitor, startup)),
+                None => println!(
+                    "Skipping `{}`, monitor {} of monitor group {} is not active",
+                    startup.command, startup.monitor, group.name,
+                ),
+            }
+        }
+    }
+    if launches.is_empty() {
+        return;
+    }
+
+    // Subscribe before executing anything so that no window is missed
+    let (sender, receiver) = mpsc::channel();
+    if launches.iter().any(|(_, _, startup)| startup.app_id.is_some()) {
+        let listen_connection = Connection::new().expect("Failed to connect to swayipc");
+        let events = listen_connection.subscribe([EventType::Window]).expect("Failed to subscribe to window events");
+        thread::spawn(move || {
+            for event in events {
+                let Ok(Event::Window(event)) = event else {
+                    continue;
+                };
+                if event.change != WindowChange::New {
+                    continue;
+                }
+                if sender.send((event.container.id, event.container.app_id)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    let mut pending = Vec::new();
+    for (workspace_id, monitor, startup) in launches {
+        // creates the workspace on its monitor if it does not exist
+        connection.run_command(get_assign_and_focus_workspace_command(&workspace_id, monitor))
+            .expect("Failed to focus workspace");
+        exec_command(connection, &startup.command);
+        if let Some(app_id) = &startup.app_id {
+            pending.push((app_id.clone(), workspace_id));
+        }
+    }
+
+    let deadline = Instant::now() + WINDOW_TIMEOUT;
+    while !pending.is_empty() {
+        let Ok((container_id, app_id)) = receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) else {
+            let app_ids: Vec<&str> = pending.iter().map(|(app_id, _)| app_id.as_str()).collect();
+            println!("Timed out waiting for windows of {}", app_ids.join(", "));
+            break;
+        };
+        // the first window with the app_id goes to the first command waiting for it
+        let Some(position) = pending.iter().position(|(pending_app_id, _)| Some(pending_app_id) == app_id.as_ref()) else {
+            continue;
+        };
+        let (_, workspace_id) = pending.remove(position);
+        move_container_by_id(connection, container_id, &workspace_id);
+    }
+}
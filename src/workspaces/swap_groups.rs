@@ -2,19 +2,16 @@ use std::collections::HashSet;
 
 use swayipc::Connection;
 
-use crate::{config::Config, sway::commands::{get_active_monitor_names, get_workspaces, rename_workspace}};
-use super::{utils::{generate_random_string, get_target_index}, workspace_id::WorkspaceId};
+use crate::{config::MonitorGroup, sway::commands::{get_active_monitor_names, get_workspaces, rename_workspace}};
+use super::{utils::{generate_random_string, get_target_index, rename_workspaces}, workspace_id::WorkspaceId};
 
 
-// TODO: Either changes this to... or add a `fn` to...
-//   take workspace group `from` and insert it at index `to` shifting all the other groups
 pub fn swap_workspace_groups(
     connection: &mut Connection,
-    config: &Config,
+    monitor_group: &MonitorGroup,
     from_index: i32,
     to: &str,
     // focus: bool,
-    mon_group: Option<&String>,
 ) {
     let active_monitors = get_active_monitor_names(connection);
     let workspaces = get_workspaces(connection);
@@ -22,9 +19,6 @@ pub fn swap_workspace_groups(
         .map(|workspace| workspace.name.clone())
         .collect();
 
-    let monitor_group = mon_group
-        .and_then(|group_name| config.get_group(group_name))
-        .unwrap_or(config.get_primary_group());
     let monitor_indices: Vec<usize> = monitor_group.monitors.iter()
         .enumerate()
         .filter(|(_, name)| active_monitors.contains(*name))
@@ -69,5 +63,52 @@ fn swap_workspaces(connection: &mut Connection, workspaces: &HashSet<String>, fr
     if workspaces.contains(to) {
         rename_workspace(connection, &tmp_id, &from);
     }
-    return true;
+    true
+}
+
+/// Take workspace group `from` and insert it at index `to` shifting all the groups
+/// in between by one towards `from`.
+pub fn insert_workspace_group(
+    connection: &mut Connection,
+    monitor_group: &MonitorGroup,
+    from_index: i32,
+    to: &str,
+) {
+    let workspaces = get_workspaces(connection);
+    let group_ids: Vec<WorkspaceId> = workspaces.iter()
+        .filter_map(|workspace| WorkspaceId::parse_safe(&workspace.name))
+        .filter(|id| id.get_monitor_group_name() == monitor_group.name)
+        .collect();
+    if !group_ids.iter().any(|id| id.get_index() == from_index) {
+        println!("Workspace group {} does not exist. Nothing was renamed.", from_index);
+        return;
+    }
+
+    let to_index = get_target_index(&workspaces, &monitor_group.name, to);
+
+    let shift = |index: i32| -> i32 {
+        if index == from_index {
+            to_index
+        } else if from_index < to_index && index > from_index && index <= to_index {
+            index - 1
+        } else if from_index > to_index && index >= to_index && index < from_index {
+            index + 1
+        } else {
+            index
+        }
+    };
+    // Rename existing workspaces on every monitor of the group, including parked ones
+    let renames: Vec<(WorkspaceId, WorkspaceId)> = group_ids.into_iter()
+        .filter(|id| shift(id.get_index()) != id.get_index())
+        .map(|id| {
+            let new_id = WorkspaceId::new(&monitor_group.name, id.get_monitor_index(), shift(id.get_index()));
+            (id, new_id)
+        })
+        .collect();
+
+    if renames.is_empty() {
+        println!("Source and destination workspace groups are the same. Nothing was renamed.");
+        return;
+    }
+    rename_workspaces(connection, &renames);
 }
@@ -3,8 +3,10 @@ use std::collections::HashSet;
 use itertools::Itertools;
 use swayipc::Connection;
 
-use crate::config::Config;
-use crate::sway::commands::{focus_workspace, get_active_outputs, get_assign_and_focus_workspace_command, get_workspaces};
+use crate::config::{Config, MonitorGroup};
+use crate::sway::commands::{
+    focus_workspace, get_active_monitor_names, get_active_outputs, get_assign_and_focus_workspace_command, get_workspaces,
+};
 use crate::sway::utils::get_output_descriptor_by_name;
 use super::utils::{find_focused_workspace, get_target_index};
 use super::workspace_id::WorkspaceId;
@@ -13,31 +15,83 @@ use super::workspace_id::WorkspaceId;
 pub fn switch_workspace_groups(connection: &mut Connection, config: &Config, monitor_group: &str, destination: &str) {
     let workspaces = get_workspaces(connection);
 
-    let next_index = get_target_index(&workspaces, monitor_group, destination);
+    let group_config = config.get_group(monitor_group)
+        .expect("Monitor group not found");
+    let next_index = group_config.bound_index(get_target_index(&workspaces, monitor_group, destination));
+
+    if group_config.is_linked() {
+        // Switch the other linked monitor groups first so that the focus ends up in the target group
+        let active_monitors = get_active_monitor_names(connection);
+        for linked_group in config.groups.iter()
+            .filter(|group| group.is_linked() && group.name != monitor_group)
+            .filter(|group| group.has_active_monitor(&active_monitors))
+        {
+            show_workspace_group(connection, config, linked_group, linked_group.bound_index(next_index), false);
+        }
+    }
+    show_workspace_group(connection, config, group_config, next_index, true);
+}
+
+/// Show workspace group `next_index` of the monitor group on all its monitors.
+/// If `take_focus` is set the focus moves to the monitor group
+/// unless it is already there or on an unrelated monitor.
+fn show_workspace_group(
+    connection: &mut Connection,
+    config: &Config,
+    group_config: &MonitorGroup,
+    next_index: i32,
+    take_focus: bool,
+) {
+    let monitor_group = group_config.name.as_str();
+    let workspaces = get_workspaces(connection);
+    let active_outputs = get_active_outputs(connection);
+    let active_outputs_flat: HashSet<String> = active_outputs.iter()
+        .flat_map(|(name, desc)| [name.clone(), desc.clone()].into_iter())
+        .collect();
+    if !group_config.has_active_monitor(&active_outputs_flat) {
+        // The workspace group can only be shown on the fallback monitor if it is parked
+        match config.get_fallback_monitor(group_config, &active_outputs_flat) {
+            Some(monitor) => {
+                let workspace_id = WorkspaceId::parked(monitor_group, next_index);
+                connection.run_command(get_assign_and_focus_workspace_command(&workspace_id, &monitor))
+                    .expect("Failed to switch workspace groups");
+            },
+            None => eprintln!("Monitor group {} has no active monitors and its workspaces are not parked", monitor_group),
+        }
+        return;
+    }
 
     // Find the workspace that should be in focus after the switch
     let focused_workspace = find_focused_workspace(&workspaces);
     let focused_workspace_id = WorkspaceId::parse_safe(&focused_workspace.name);
-    let monitor_index_to_focus = focused_workspace_id.and_then(|id| {
+    let focused_monitor_index = focused_workspace_id.and_then(|id| {
         // focused workspace is managed, was able to parse the ID
         if id.get_monitor_group_name() == monitor_group {
             // the focused workspace is in the target group
-            Some(id.get_monitor_index())
+            id.get_monitor_index()
         } else {
             // the focused workspace is not in the target group
             None
         }
-    }).or(
-        // focused workspace is not managed, find the index of the monitor if
-        // it is part of the target monitor group to switch to a managed workspace
-        config.get_group(monitor_group).and_then(
-            |group| group.get_monitor_index(&focused_workspace.output)
+    });
+    let monitor_index_to_focus = if !take_focus {
+        focused_monitor_index
+    } else {
+        focused_monitor_index.or_else(||
+            // focused workspace is not in the target group, use the configured monitor to focus
+            group_config.settings.focus_monitor.filter(|index| group_config.monitors.get(*index)
+                .is_some_and(|monitor| active_outputs_flat.contains(monitor))
+            )
+        ).or_else(||
+            // focused workspace is not managed, find the index of the monitor if
+            // it is part of the target monitor group to switch to a managed workspace
+            group_config.get_monitor_index(&focused_workspace.output)
                 // The monitor might be configured using its descriptor, so try looking that up too
                 .or_else(|| get_output_descriptor_by_name(connection, &focused_workspace.output)
-                    .and_then(|descriptor| group.get_monitor_index(&descriptor))
+                    .and_then(|descriptor| group_config.get_monitor_index(&descriptor))
                 )
         )
-    );
+    };
     // If the monitor index to focus is None, keep the original focus
     // because the workspace is not in target monitor group
     let next_focus = monitor_index_to_focus
@@ -45,13 +99,7 @@ pub fn switch_workspace_groups(connection: &mut Connection, config: &Config, mon
             WorkspaceId::new(monitor_group, focused_monitor_index,next_index).to_string()
         ).unwrap_or(focused_workspace.name.clone());
 
-    let active_outputs = get_active_outputs(connection);
-    let active_outputs_flat: HashSet<String> = active_outputs.iter()
-        .flat_map(|(name, desc)| [name.clone(), desc.clone()].into_iter())
-        .collect();
     // Switch the workspaces
-    let group_config = config.get_group(monitor_group)
-        .expect("Monitor group not found");
     let commands = group_config.monitors.iter()
         .enumerate()
         .filter(|(_, monitor)| active_outputs_flat.contains(*monitor))
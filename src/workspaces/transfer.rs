@@ -0,0 +1,72 @@
+use std::collections::HashSet;
+
+use itertools::Itertools;
+use swayipc::Connection;
+
+use crate::config::{MergeLayout, MonitorGroup};
+use crate::sway::commands::{get_active_monitor_names, get_workspaces, move_workspace_to_monitor, rename_workspace};
+use super::utils::move_all_containers;
+use super::workspace_id::WorkspaceId;
+
+
+/// Hand over workspace group `index` of one monitor group to another monitor group
+/// as workspace group `new_index`. Workspaces are assigned to the active monitors
+/// of the destination in order, containers of surplus workspaces are merged into
+/// the workspace on the main monitor of the destination group.
+pub fn transfer_workspace_group(
+    connection: &mut Connection,
+    index: i32,
+    from_group: &MonitorGroup,
+    to_group: &MonitorGroup,
+    new_index: i32,
+    merge_layout: Option<MergeLayout>,
+) {
+    if from_group.name == to_group.name && index == new_index {
+        println!("Source and destination are the same. Nothing was transferred.");
+        return;
+    }
+    let active_monitors = get_active_monitor_names(connection);
+    let workspaces = get_workspaces(connection);
+    let workspace_names: HashSet<String> = workspaces.iter()
+        .map(|workspace| workspace.name.clone())
+        .collect();
+
+    let source_ids: Vec<WorkspaceId> = workspaces.iter()
+        .filter_map(|workspace| WorkspaceId::parse_safe(&workspace.name))
+        .filter(|id| id.get_monitor_group_name() == from_group.name && id.get_index() == index)
+        // parked workspaces come last
+        .sorted_by_key(|id| (id.is_parked(), id.get_monitor_index()))
+        .collect();
+    if source_ids.is_empty() {
+        println!("Workspace group {} does not exist in {}. Nothing was transferred.", index, from_group.name);
+        return;
+    }
+
+    let destination_monitors: Vec<(usize, &String)> = to_group.monitors.iter()
+        .enumerate()
+        .filter(|(_, monitor)| active_monitors.contains(*monitor))
+        .collect();
+    if destination_monitors.is_empty() {
+        eprintln!("Monitor group {} has no active monitors. Nothing was transferred.", to_group.name);
+        return;
+    }
+    // the first active monitor is the main monitor
+    let main_monitor_id = WorkspaceId::new(&to_group.name, destination_monitors[0].0, new_index);
+
+    for (position, source_id) in source_ids.iter().enumerate() {
+        let Some((monitor_index, monitor)) = destination_monitors.get(position) else {
+            // There are more workspaces than destination monitors
+            move_all_containers(connection, source_id, &main_monitor_id, merge_layout);
+            continue;
+        };
+
+        let target_id = WorkspaceId::new(&to_group.name, *monitor_index, new_index);
+        if workspace_names.contains(&target_id.to_string()) {
+            move_all_containers(connection, source_id, &target_id, merge_layout);
+        } else {
+            rename_workspace(connection, source_id, &target_id);
+            move_workspace_to_monitor(connection, &target_id, monitor);
+            println!("Rename workspace {} to {} on monitor {}", source_id, target_id, monitor);
+        }
+    }
+}
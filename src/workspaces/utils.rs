@@ -1,7 +1,11 @@
+use itertools::Itertools;
 use rand::{Rng, distr::Alphanumeric};
 use regex::Regex;
-use swayipc::Workspace;
+use swayipc::{Connection, Workspace};
 
+use crate::config::MergeLayout;
+use crate::sway::commands::{get_rename_workspace_command, get_workspace_tree};
+use super::plan::{execute_plan, plan_container_migration};
 use super::workspace_id::WorkspaceId;
 
 
@@ -53,3 +57,56 @@ pub(super) fn generate_random_string(length: usize) -> String {
         .map(char::from)
         .collect()
 }
+
+/// Move every container (tiled and floating) from one workspace to another
+/// preserving their relative order. Containers are appended to the target
+/// workspace so the target workspace keeps its own containers first.
+/// Floating containers keep their relative position and size, tiled containers
+/// are wrapped in a single container if `layout` is given.
+/// Return `false` if the source workspace does not exist.
+/// 
+/// # Panics
+/// Panics if any of the sway IPC requests fail
+pub(super) fn move_all_containers<IdA: std::fmt::Display, IdB: std::fmt::Display>(
+    connection: &mut Connection,
+    from: &IdA,
+    to: &IdB,
+    layout: Option<MergeLayout>,
+) -> bool {
+    let Some(workspace_tree) = get_workspace_tree(connection, from) else {
+        return false;
+    };
+    let plan = plan_container_migration(
+        &workspace_tree,
+        &to.to_string(),
+        layout,
+        &format!("merge workspace {}", from),
+    );
+    execute_plan(connection, &plan, false);
+    true
+}
+
+/// Rename many workspaces at once in a single batch of commands.
+/// All workspaces are first renamed to temporary names so that the new names
+/// can overlap with the old ones (e.g. when shifting or permuting indices).
+/// 
+/// # Panics
+/// Panics if the command fails
+pub(super) fn rename_workspaces<IdA: std::fmt::Display, IdB: std::fmt::Display>(
+    connection: &mut Connection,
+    renames: &[(IdA, IdB)],
+) {
+    if renames.is_empty() {
+        return;
+    }
+    let tmp_prefix = format!("tmp-{}", generate_random_string(6));
+    let commands = renames.iter()
+        .enumerate()
+        .map(|(i, (old, _))| get_rename_workspace_command(old, &format!("{}-{}", tmp_prefix, i)))
+        .chain(renames.iter()
+            .enumerate()
+            .map(|(i, (_, new))| get_rename_workspace_command(&format!("{}-{}", tmp_prefix, i), new))
+        )
+        .join(";");
+    connection.run_command(commands).expect("Failed to rename workspaces");
+}
@@ -1,29 +1,40 @@
 use regex::Regex;
 
 
+/// Monitor slot in the names of workspaces parked outside of their monitor group
+const PARKED_MONITOR_SLOT: &str = "parked";
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct WorkspaceId {
     monitor_group_name: String,
-    monitor_index: usize,
+    /// `None` if the workspace is parked
+    monitor_index: Option<usize>,
     workspace_group_index: i32,
 }
 
 impl WorkspaceId {
-    pub fn new(group_name: &str, monitor_index: usize, workspace_group_index: i32) -> Self {
-        return Self {
+    /// Pass `None` as the monitor index to get the id of a parked workspace
+    pub fn new(group_name: &str, monitor_index: impl Into<Option<usize>>, workspace_group_index: i32) -> Self {
+        Self {
             monitor_group_name: group_name.to_string(),
-            monitor_index,
+            monitor_index: monitor_index.into(),
             workspace_group_index,
-        };
+        }
+    }
+
+    /// Workspace parked on a fallback output outside of its monitor group
+    pub fn parked(group_name: &str, workspace_group_index: i32) -> Self {
+        Self::new(group_name, None, workspace_group_index)
     }
 
     pub fn parse_safe(name: &str) -> Option<WorkspaceId> {
-        let workspace_id_re = Regex::new(r"^(\w+)-(\d+)-(-?\d+)$").unwrap();
+        let workspace_id_re = Regex::new(r"^(\w+)-(\d+|parked)-(-?\d+)$").unwrap();
         workspace_id_re
             .captures(name)
             .map(|caps| WorkspaceId {
                 monitor_group_name: caps[1].to_string(),
-                monitor_index: caps[2].parse().expect("Failed to parse monitor index"),
+                monitor_index: (&caps[2] != PARKED_MONITOR_SLOT)
+                    .then(|| caps[2].parse().expect("Failed to parse monitor index")),
                 workspace_group_index: caps[3].parse().expect("Failed to parse workspace group index"),
             })
     }
@@ -35,27 +46,31 @@ impl WorkspaceId {
         }
     }
 
-    pub fn to_string(&self) -> String {
-        return format!("{}-{}-{}", self.monitor_group_name, self.monitor_index, self.workspace_group_index);
-    }
-
     /// Return the index of the workspace group
     pub fn get_index(&self) -> i32 {
-        return self.workspace_group_index;
+        self.workspace_group_index
     }
 
     // Return name of the monitor group
     pub fn get_monitor_group_name(&self) -> &str {
-        return &self.monitor_group_name;
+        &self.monitor_group_name
+    }
+
+    // Return the index of the monitor within its group, `None` if the workspace is parked
+    pub fn get_monitor_index(&self) -> Option<usize> {
+        self.monitor_index
     }
 
-    // Return the index of the monitor within its group
-    pub fn get_monitor_index(&self) -> usize {
-        return self.monitor_index;
+    /// Whether the workspace is parked on a fallback output outside of its monitor group
+    pub fn is_parked(&self) -> bool {
+        self.monitor_index.is_none()
     }
 }
 impl std::fmt::Display for WorkspaceId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_string())
+        match self.monitor_index {
+            Some(monitor_index) => write!(f, "{}-{}-{}", self.monitor_group_name, monitor_index, self.workspace_group_index),
+            None => write!(f, "{}-{}-{}", self.monitor_group_name, PARKED_MONITOR_SLOT, self.workspace_group_index),
+        }
     }
 }